@@ -10,6 +10,7 @@
 //! # use mlog_parse::parser::statements::Statement;
 //! # use mlog_parse::parser::args::Argument;
 //! # use mlog_parse::parser::args::ConditionOp;
+//! # use mlog_parse::parser::statements::JumpTarget;
 //! const SRC: &str = r#"
 //!     loop_start:
 //!         op add i i 1
@@ -37,7 +38,7 @@
 //!             index: Argument::Number(0.)
 //!         },
 //!         Statement::Jump {
-//!            index: 0,
+//!            target: JumpTarget::labelled(0, "loop_start"),
 //!            cond: ConditionOp::LessThan,
 //!            lhs: Some(Argument::Variable("i")),
 //!            rhs: Some(Argument::Number(5.)),
@@ -51,6 +52,10 @@
 
 /// The module for parsing
 pub mod parser;
+pub mod printer;
+pub mod program;
+/// The module for running programs
+pub mod vm;
 
 #[cfg(test)]
 mod tests;
@@ -96,40 +96,40 @@ impl Rgba {
     /// Parses a colour literal from the format %[name]. Only use this if the literal has already
     /// been checked (e.g. from a regex).
     ///
-    /// Returns [`Some`] if the named colour exists, and [`None`] otherwise.
-    ///
-    /// # Panics
-    /// On any problem, at all.
+    /// Returns [`Some`] if the named colour exists, and [`None`] otherwise (including when the
+    /// literal isn't in the right format).
     pub(super) fn from_named_literal_unchecked(literal: &str) -> Option<Self> {
-        let colour_name = &literal[2..literal.len() - 1].to_lowercase();
+        let colour_name = literal
+            .strip_prefix("%[")?
+            .strip_suffix(']')?
+            .to_lowercase();
         NAMED_COLOURS.get(colour_name.as_str()).copied()
     }
 
     /// Parses a colour literal in the format %RRGGBB or %RRGGBBAA. Only use this if the literal
     /// has already been checked (e.g. from a regex).
     ///
-    /// # Panics
-    /// On any problem, at all.
+    /// Channels that can't be parsed are set to 0 instead of panicking.
     pub(super) fn from_hex_literal_unchecked(literal: &str) -> Self {
         let mut channels = (1..literal.len())
             .step_by(2)
             .map(|start| literal.get(start..start + 2));
         let mut get_channel = || {
-            let Some(Some(channel)) = channels.next() else {
-                return u8::from_str_radix("", 16);
-            };
-
-            Ok(i16::from_str_radix(channel, 16).unwrap() as u8)
+            channels
+                .next()
+                .flatten()
+                .and_then(|channel| i16::from_str_radix(channel, 16).ok())
+                .map_or(0, |channel| channel as u8)
         };
         let mut color = Self::default();
         let Self { r, g, b, a } = &mut color;
 
         for channel in [r, g, b] {
-            *channel = get_channel().unwrap();
+            *channel = get_channel();
         }
 
         if literal.len() == 9 {
-            *a = get_channel().unwrap()
+            *a = get_channel()
         }
 
         color
@@ -11,7 +11,8 @@ mod num_parse;
 #[cfg(test)]
 mod test;
 
-use num_parse::parse_nradix_literal;
+use crate::vm::{Value, ops};
+use num_parse::parse_number;
 use regex::RegexSet;
 use std::fmt;
 use std::sync::LazyLock;
@@ -23,7 +24,8 @@ pub use colour::Rgba;
 #[derive(Debug, PartialEq, Copy, Clone, PartialOrd)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Argument<'a> {
-    /// A numeric argument (e.g. `5`, `0x2763`, `0b0011s`)
+    /// A numeric argument (e.g. `5`, `-.5`, `1e12`, `0x2763`, `0b0011`). This follows the game's
+    /// rules for what counts as a number, so some unusual tokens (like `5f`) are numbers too.
     Number(f64),
     /// A literal string (e.g. `"hello world"`)
     String(&'a str),
@@ -53,16 +55,15 @@ static ARG_PATTERNS: LazyLock<RegexSet> = LazyLock::new(|| {
     RegexSet::new([
         "^\".*\"$",
         "^%[0-9a-fA-F]{6}(?:[0-9a-fA-F]{2})?$",
-        "^%[.*]",
+        r"^%\[.+\]$",
         "^@",
-        r"^[+-]?\d+(?:\.\d+)?$",
-        "^[+-]?0x[0-9a-fA-F]+$",
-        "^[+-]?0b[01]+$",
     ])
     .unwrap()
 });
 
 impl<'s> From<&'s str> for Argument<'s> {
+    /// Parses an argument from a token. This never panics: anything that isn't a valid literal is
+    /// treated as a variable, which is also what the game does.
     fn from(value: &'s str) -> Self {
         let matches = ARG_PATTERNS.matches(value);
         match () {
@@ -76,18 +77,10 @@ impl<'s> From<&'s str> for Argument<'s> {
                 }
             }
             _ if matches.matched(3) => Argument::GlobalVar(&value[1..]),
-            _ if matches.matched(4) => {
-                let first = value.as_bytes().first().copied().unwrap_or_default();
-                let value = matches!(first, b'-' | b'+')
-                    .then(|| &value[1..])
-                    .unwrap_or(value);
-                Argument::Number(
-                    value.parse::<f64>().unwrap() * if first == b'-' { -1. } else { 1. },
-                )
-            }
-            _ if matches.matched(5) => Argument::Number(parse_nradix_literal(value, 16) as f64),
-            _ if matches.matched(6) => Argument::Number(parse_nradix_literal(value, 2) as f64),
-            _ => Argument::Variable(value),
+            _ => match parse_number(value) {
+                Some(x) => Argument::Number(x),
+                None => Argument::Variable(value),
+            },
         }
     }
 }
@@ -126,6 +119,55 @@ pub enum ConditionOp {
     Always,
 }
 
+impl ConditionOp {
+    /// Checks the condition on two values, the same way `jump` and `select` do. This is the same
+    /// as [`ops::condition`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use mlog_parse::parser::args::ConditionOp;
+    /// # use mlog_parse::vm::Value;
+    /// assert!(ConditionOp::Equal.eval(&Value::Null, &Value::Number(0.)));
+    /// assert!(!ConditionOp::StrictEqual.eval(&Value::Null, &Value::Number(0.)));
+    /// ```
+    #[must_use]
+    pub fn eval(self, a: &Value, b: &Value) -> bool {
+        ops::condition(self, a, b)
+    }
+
+    /// Gets the condition that's true exactly when this one is false (e.g. `lessThan` becomes
+    /// `greaterThanEq`). There is no condition that's never true, so this is [`None`] for
+    /// [`ConditionOp::Always`].
+    #[must_use]
+    pub fn inverted(self) -> Option<Self> {
+        Some(match self {
+            Self::Equal => Self::NotEqual,
+            Self::NotEqual => Self::Equal,
+            Self::StrictEqual => Self::StrictNotEqual,
+            Self::StrictNotEqual => Self::StrictEqual,
+            Self::LessThan => Self::GreaterThanEq,
+            Self::LessThanEq => Self::GreaterThan,
+            Self::GreaterThan => Self::LessThanEq,
+            Self::GreaterThanEq => Self::LessThan,
+            Self::Always => return None,
+        })
+    }
+
+    /// Gets the condition that gives the same result with the operands swapped (e.g. `a lessThan
+    /// b` is the same as `b greaterThan a`)
+    #[must_use]
+    pub fn swapped(self) -> Self {
+        match self {
+            Self::LessThan => Self::GreaterThan,
+            Self::LessThanEq => Self::GreaterThanEq,
+            Self::GreaterThan => Self::LessThan,
+            Self::GreaterThanEq => Self::LessThanEq,
+            x => x,
+        }
+    }
+}
+
 impl fmt::Display for ConditionOp {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(
@@ -1,13 +1,91 @@
 //! General functions to parse numbers
+//!
+//! These are ports of the number parsing in the game (`LAssembler.parseDouble`, and
+//! `Strings.parseDouble`/`Strings.parseLong` from Arc), so that a token is a number exactly when
+//! the game treats it as one. None of them panic, and anything the game can't parse (including
+//! out of range integers) gives [`None`].
 
-/// Parses a literal with a prefix (e.g. 0x05) with a given radix..
-pub(super) fn parse_nradix_literal(text: &str, radix: u32) -> i64 {
-    let mut chars = text.chars();
+/// Parses an integer from `text[start..end]` with the given radix. A single leading `+` or `-` is
+/// allowed.
+pub(super) fn parse_long(text: &[u8], radix: u32, start: usize, end: usize) -> Option<i64> {
+    let digits = text.get(start..end)?;
+    let (negative, digits) = match digits {
+        [b'-', rest @ ..] => (true, rest),
+        [b'+', rest @ ..] => (false, rest),
+        x => (false, x),
+    };
 
-    match chars.next().unwrap() {
-        sign @ ('+' | '-') => {
-            i64::from_str_radix(&text[3..], radix).unwrap() * if sign == '-' { -1 } else { 1 }
+    if digits.is_empty() {
+        return None;
+    }
+
+    // Accumulated negatively, so that i64::MIN can be parsed
+    let mut result = 0i64;
+    for digit in digits {
+        let digit = (*digit as char).to_digit(radix)?;
+        result = result
+            .checked_mul(radix.into())?
+            .checked_sub(digit.into())?;
+    }
+
+    if negative {
+        Some(result)
+    } else {
+        result.checked_neg()
+    }
+}
+
+/// Parses a decimal number. Besides the usual formats, this accepts a trailing `f`, `F` or `.`
+/// (e.g. `5.`), a missing whole part (e.g. `.5`) and an exponent on integers (e.g. `1e12`).
+pub(super) fn parse_double(text: &str) -> Option<f64> {
+    let bytes = text.as_bytes();
+    let (&first, &last) = (bytes.first()?, bytes.last()?);
+
+    let start = usize::from(matches!(first, b'+' | b'-'));
+    let end = bytes.len() - usize::from(matches!(last, b'f' | b'F' | b'.'));
+    let sign = if first == b'-' { -1. } else { 1. };
+
+    if end == 0 {
+        return None;
+    }
+
+    let find_last = |c: &[u8]| (start..end).rev().find(|x| c.contains(&bytes[*x]));
+
+    if let Some(dot) = find_last(b".") {
+        let whole = if start == dot {
+            0
+        } else {
+            parse_long(bytes, 10, start, dot)?
+        };
+        let decimal = parse_long(bytes, 10, dot + 1, end).filter(|x| *x >= 0)?;
+        let decimal = decimal as f64 / 10f64.powf((end - dot - 1) as f64);
+
+        Some((whole as f64 + decimal.copysign(whole as f64)) * sign)
+    } else if let Some(e) = find_last(b"eE") {
+        let whole = parse_long(bytes, 10, start, e)?;
+        let power = parse_long(bytes, 10, e + 1, end)?;
+
+        Some(whole as f64 * 10f64.powf(power as f64) * sign)
+    } else {
+        Some(parse_long(bytes, 10, start, end)? as f64 * sign)
+    }
+}
+
+/// Parses a numeric literal, including ones with a radix prefix (`0x`/`0b`, optionally with a sign
+/// before them). The prefixes are case sensitive, like in the game.
+pub(super) fn parse_number(text: &str) -> Option<f64> {
+    let bytes = text.as_bytes();
+
+    for (prefix, radix) in [("0b", 2), ("0x", 16)] {
+        if let Some(rest) = text.strip_prefix(prefix) {
+            return parse_long(rest.as_bytes(), radix, 0, rest.len()).map(|x| x as f64);
+        }
+        for (sign, mul) in [('+', 1.), ('-', -1.)] {
+            if text.starts_with(sign) && text[1..].starts_with(prefix) {
+                return parse_long(bytes, radix, 3, bytes.len()).map(|x| x as f64 * mul);
+            }
         }
-        _ => i64::from_str_radix(&text[2..], radix).unwrap(),
     }
+
+    parse_double(text)
 }
@@ -1,5 +1,6 @@
 use std::str::FromStr;
-use crate::parser::args::{Argument, Rgba, colour::ColourParseError};
+use crate::parser::args::{Argument, ConditionOp, Rgba, colour::ColourParseError};
+use crate::vm::Value;
 
 #[test]
 fn colour_literal_unchecked() {
@@ -129,3 +130,89 @@ fn display() {
         "%ffffffff".to_string()
     )
 }
+
+#[test]
+fn numbers() {
+    assert_eq!(Argument::from("1e12"), Argument::Number(1e12));
+    assert_eq!(Argument::from("1e-15"), Argument::Number(1e-15));
+    assert_eq!(Argument::from(".5"), Argument::Number(0.5));
+    assert_eq!(Argument::from("-.5"), Argument::Number(-0.5));
+    assert_eq!(Argument::from("5."), Argument::Number(5.));
+    assert_eq!(Argument::from("5f"), Argument::Number(5.));
+    assert_eq!(Argument::from("0xFF"), Argument::Number(255.));
+    assert_eq!(Argument::from("-0b101"), Argument::Number(-5.));
+
+    // Not numbers in the game
+    assert_eq!(Argument::from("0XFF"), Argument::Variable("0XFF"));
+    assert_eq!(Argument::from("1.5e3"), Argument::Variable("1.5e3"));
+    assert_eq!(Argument::from("1e"), Argument::Variable("1e"));
+    assert_eq!(Argument::from("e5"), Argument::Variable("e5"));
+    assert_eq!(Argument::from("inf"), Argument::Variable("inf"));
+    assert_eq!(Argument::from("f"), Argument::Variable("f"));
+    assert_eq!(Argument::from("-"), Argument::Variable("-"));
+    assert_eq!(Argument::from("."), Argument::Variable("."));
+}
+
+#[test]
+fn out_of_range_numbers() {
+    assert_eq!(
+        Argument::from("0x7fffffffffffffff"),
+        Argument::Number(i64::MAX as f64)
+    );
+    assert_eq!(
+        Argument::from("0xffffffffffffffffff"),
+        Argument::Variable("0xffffffffffffffffff")
+    );
+    assert_eq!(
+        Argument::from("99999999999999999999"),
+        Argument::Variable("99999999999999999999")
+    );
+    assert_eq!(Argument::from("1e400"), Argument::Number(f64::INFINITY));
+}
+
+#[test]
+fn condition_ops() {
+    use ConditionOp as C;
+
+    let ops = [
+        C::Equal,
+        C::NotEqual,
+        C::StrictEqual,
+        C::StrictNotEqual,
+        C::LessThan,
+        C::LessThanEq,
+        C::GreaterThan,
+        C::GreaterThanEq,
+        C::Always,
+    ];
+    let values = [
+        Value::Null,
+        Value::Number(0.),
+        Value::Number(1.),
+        Value::Number(1.0000001),
+        Value::Number(-2.),
+        Value::from("a"),
+        Value::from("b"),
+        Value::Content("copper".into()),
+    ];
+
+    for op in ops {
+        for a in &values {
+            for b in &values {
+                if let Some(inverted) = op.inverted() {
+                    assert_ne!(op.eval(a, b), inverted.eval(a, b), "{op} {a} {b}");
+                    assert_eq!(inverted.inverted(), Some(op));
+                }
+                assert_eq!(op.eval(a, b), op.swapped().eval(b, a), "{op} {a} {b}");
+            }
+        }
+    }
+
+    assert!(C::Equal.eval(&Value::Number(1.), &Value::Number(1.0000001)));
+    assert!(!C::StrictEqual.eval(&Value::Number(1.), &Value::Number(1.0000001)));
+    assert!(C::Equal.eval(&Value::from("a"), &Value::Number(1.)));
+    assert!(!C::Equal.eval(&Value::from("a"), &Value::from("b")));
+    assert_eq!(C::Always.inverted(), None);
+    assert_eq!(C::LessThan.inverted(), Some(C::GreaterThanEq));
+    assert_eq!(C::LessThan.swapped(), C::GreaterThan);
+}
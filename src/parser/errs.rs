@@ -1,6 +1,6 @@
 //! This module implements error handling for the parser.
 
-use crate::parser::statements;
+use crate::parser::{span::Span, statements};
 use std::{error::Error, fmt};
 
 /// An error found when parsing
@@ -8,8 +8,8 @@ use std::{error::Error, fmt};
 pub enum ParseError<'s> {
     /// Any invalid statement
     Statement {
-        /// The line it occurred on (in the source)
-        line: usize,
+        /// Where the statement is in the source
+        span: Span,
         /// The specific error that occurred
         error: statements::ParseError<'s>,
     },
@@ -21,14 +21,19 @@ impl fmt::Display for ParseError<'_> {
             f,
             "{}",
             match self {
-                Self::Statement { line, error } => {
+                Self::Statement { span, error } => {
                     match error {
                         statements::ParseError::MissingJumpLabel(x) => {
-                            format!("The jump label {x} is missing (used on line {line})")
+                            format!("The jump label {x} is missing (used at {span})")
                         }
-                        statements::ParseError::InvalidInstruction(x) => format!(
-                            "The instruction \"{}\" is invalid  (line {line})",
-                            x.join(",")
+                        statements::ParseError::InvalidInstruction(x) => {
+                            format!("The instruction \"{}\" is invalid (at {span})", x.join(","))
+                        }
+                        statements::ParseError::TooFewOperands { expected, actual } => format!(
+                            "Expected at least {expected} operands, but got {actual} (at {span})"
+                        ),
+                        statements::ParseError::TooManyOperands { expected, actual } => format!(
+                            "Expected at most {expected} operands, but got {actual} (at {span})"
                         ),
                     }
                 }
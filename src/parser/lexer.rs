@@ -1,11 +1,17 @@
 //! The "main" part of the parser. This handles parsing a file into statements.
 
-use crate::parser::{errs::ParseError, statements::StatementType};
-use regex::Regex;
-use std::{collections::HashMap, marker::PhantomData, sync::LazyLock};
+use crate::parser::{
+    errs::ParseError,
+    span::{Span, Spanned, SpannedStatement},
+    statements::{ParseOptions, StatementType},
+};
+use std::{collections::HashMap, marker::PhantomData};
 
 /// A lexer for mindustry logic.
 ///
+/// Lexing and parsing never panic, whatever the input is. Anything that can't be parsed is
+/// returned as a [`ParseError`] instead.
+///
 /// # Examples
 ///
 /// ```
@@ -14,6 +20,7 @@ use std::{collections::HashMap, marker::PhantomData, sync::LazyLock};
 /// # use mlog_parse::parser::statements::Statement;
 /// # use mlog_parse::parser::args::Argument;
 /// # use mlog_parse::parser::args::ConditionOp;
+/// # use mlog_parse::parser::statements::JumpTarget;
 /// const SRC: &str = r#"
 ///     loop_start:
 ///         op add i i 1
@@ -41,7 +48,7 @@ use std::{collections::HashMap, marker::PhantomData, sync::LazyLock};
 ///             index: Argument::Number(0.)
 ///         },
 ///         Statement::Jump {
-///            index: 0,
+///            target: JumpTarget::labelled(0, "loop_start"),
 ///            cond: ConditionOp::LessThan,
 ///            lhs: Some(Argument::Variable("i")),
 ///            rhs: Some(Argument::Number(5.)),
@@ -51,16 +58,97 @@ use std::{collections::HashMap, marker::PhantomData, sync::LazyLock};
 /// # }
 /// ```
 pub struct Lexer<'a, T: StatementType<'a>> {
-    /// (line number, line)
-    lines: Vec<(usize, &'a str)>,
+    /// The tokens of each statement
+    lines: Vec<Vec<Spanned<&'a str>>>,
     jump_labels: HashMap<&'a str, usize>,
+    options: ParseOptions,
     index: usize,
     _marker: PhantomData<T>,
 }
 
-static JUMPLABEL_REGEX: LazyLock<Regex> =
-    LazyLock::new(|| Regex::new(r"^\s*?[[:word:]]+:").unwrap());
-static COMMENT_REGEX: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"^\s*#").unwrap());
+/// Splits the source into statements and tokens, the same way the game's `LParser` does.
+///
+/// Statements are separated by newlines or `;`, and `#` starts a comment that runs to the end of
+/// the line. A token starting with `"` is a string literal, which runs up to (and including) the
+/// next `"` on the same line, so strings can contain spaces, `;` and `#`.
+///
+/// Returns the tokens of each statement, with empty statements removed.
+fn tokenize(src: &str) -> Vec<Vec<Spanned<&str>>> {
+    let bytes = src.as_bytes();
+    let mut statements = Vec::new();
+    let mut tokens = Vec::new();
+    let mut line = 0usize;
+    let mut line_start = 0usize;
+    let mut pos = 0usize;
+
+    let spanned = |start: usize, end: usize, line: usize, line_start: usize| Spanned {
+        value: &src[start..end],
+        span: Span {
+            start,
+            end,
+            line,
+            column: src[line_start..start].chars().count(),
+        },
+    };
+
+    while pos < bytes.len() {
+        match bytes[pos] {
+            b'\n' | b';' => {
+                if !tokens.is_empty() {
+                    statements.push(std::mem::take(&mut tokens));
+                }
+                if bytes[pos] == b'\n' {
+                    line += 1;
+                    line_start = pos + 1;
+                }
+                pos += 1;
+            }
+            b'#' => {
+                while pos < bytes.len() && bytes[pos] != b'\n' {
+                    pos += 1;
+                }
+            }
+            b'"' => {
+                let start = pos;
+                pos += 1;
+                while pos < bytes.len() && !matches!(bytes[pos], b'"' | b'\n') {
+                    pos += 1;
+                }
+                // Include the closing quote if there is one
+                if pos < bytes.len() && bytes[pos] == b'"' {
+                    pos += 1;
+                }
+                tokens.push(spanned(start, pos, line, line_start));
+            }
+            x if x.is_ascii_whitespace() => pos += 1,
+            _ => {
+                let start = pos;
+                while pos < bytes.len()
+                    && !matches!(bytes[pos], b'\n' | b';' | b'#')
+                    && !bytes[pos].is_ascii_whitespace()
+                {
+                    pos += 1;
+                }
+                tokens.push(spanned(start, pos, line, line_start));
+            }
+        }
+    }
+
+    if !tokens.is_empty() {
+        statements.push(tokens);
+    }
+
+    statements
+}
+
+/// Gets the span covering all of the tokens of a statement
+fn statement_span(tokens: &[Spanned<&str>]) -> Span {
+    match tokens {
+        [first, .., last] => first.span.to(last.span),
+        [only] => only.span,
+        [] => Span::default(),
+    }
+}
 
 impl<'a, T: StatementType<'a>> Lexer<'a, T> {
     /// Renames synonymous tokens as necessary
@@ -93,47 +181,158 @@ impl<'a, T: StatementType<'a>> Lexer<'a, T> {
     pub fn new(str: &'a str) -> Self {
         let mut lines = Vec::new();
         let mut jump_labels = HashMap::new();
-        let mut statement_index = 0usize;
-
-        for (line_num, line) in str
-            .split(&['\n', ';'])
-            .enumerate()
-            .filter(|(_, x)| x.contains(|x: char| !x.is_whitespace()))
-        {
-            if COMMENT_REGEX.is_match(line) {
-            } else if JUMPLABEL_REGEX.is_match(line) {
-                jump_labels.insert(line.trim().strip_suffix(":").unwrap(), statement_index);
+
+        for tokens in tokenize(str) {
+            // Like in the game, a jump label has to be on its own
+            if let [label] = tokens.as_slice()
+                && let Some(label) = label.value.strip_suffix(':')
+            {
+                jump_labels.insert(label, lines.len());
             } else {
-                lines.push((line_num, line));
-                statement_index += 1;
+                lines.push(tokens);
             }
         }
 
         Self {
             lines,
             jump_labels,
+            options: ParseOptions::default(),
             index: 0,
             _marker: PhantomData {},
         }
     }
-}
 
-impl<'a, T: StatementType<'a>> Iterator for Lexer<'a, T> {
-    type Item = Result<T, ParseError<'a>>;
+    /// Sets the options used to parse statements.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use mlog_parse::parser::lexer::Lexer;
+    /// # use mlog_parse::parser::statements::{Arity, ParseError, ParseOptions, Statement};
+    /// let options = ParseOptions {
+    ///     arity: Arity::Strict,
+    ///     ..Default::default()
+    /// };
+    /// let mut lexer: Lexer<Statement> = Lexer::new("op add x").with_options(options);
+    ///
+    /// assert!(matches!(
+    ///     lexer.next(),
+    ///     Some(Err(mlog_parse::parser::ParseError::Statement {
+    ///         error: ParseError::TooFewOperands { expected: 3, actual: 1 },
+    ///         ..
+    ///     }))
+    /// ));
+    /// ```
+    #[must_use]
+    pub fn with_options(mut self, options: ParseOptions) -> Self {
+        self.options = options;
+        self
+    }
 
-    fn next(&mut self) -> Option<Self::Item> {
-        let (line_num, line) = self.lines.get(self.index)?;
-        let split: Vec<_> = line.split_whitespace().collect();
+    /// Gets the jump labels in the source, along with the index of the statement each one points
+    /// to.
+    #[must_use]
+    pub fn jump_labels(&self) -> &HashMap<&'a str, usize> {
+        &self.jump_labels
+    }
+
+    /// Parses the next statement, keeping the spans of it and its tokens.
+    fn next_spanned(&mut self) -> Option<Result<SpannedStatement<'a, T>, ParseError<'a>>> {
+        let tokens = self.lines.get(self.index)?;
+        let span = statement_span(tokens);
+        let values: Vec<_> = tokens.iter().map(|x| x.value).collect();
 
         self.index += 1;
 
         Some(
-            T::try_parse(&Self::do_renaming(&split), &self.jump_labels).map_err(|e| {
-                ParseError::Statement {
-                    line: *line_num,
-                    error: e,
-                }
-            }),
+            T::try_parse(&Self::do_renaming(&values), &self.jump_labels, self.options)
+                .map(|statement| SpannedStatement {
+                    statement,
+                    span,
+                    tokens: tokens.clone(),
+                })
+                .map_err(|error| ParseError::Statement { span, error }),
         )
     }
+
+    /// Turns this into an iterator over statements along with their spans in the source.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use mlog_parse::parser::lexer::Lexer;
+    /// # use mlog_parse::parser::statements::Statement;
+    /// let src = "set a 1; print \"hi\"";
+    /// let lexer: Lexer<Statement> = Lexer::new(src);
+    ///
+    /// let spans: Vec<_> = lexer.spanned().map(|x| x.unwrap().span).collect();
+    /// assert_eq!(&src[spans[1].start..spans[1].end], "print \"hi\"");
+    /// assert_eq!(spans[1].column, 9);
+    /// ```
+    pub fn spanned(
+        mut self,
+    ) -> impl Iterator<Item = Result<SpannedStatement<'a, T>, ParseError<'a>>> {
+        std::iter::from_fn(move || self.next_spanned())
+    }
+
+    /// Parses the whole source, collecting every error instead of stopping at the first one.
+    ///
+    /// Statements that fail to parse are left out of [`ParsedSource::statements`], so use the
+    /// spans (rather than the positions in the list) to find where each statement came from.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use mlog_parse::parser::lexer::Lexer;
+    /// # use mlog_parse::parser::statements::Statement;
+    /// let src = "set a 1\nop dv a a 2\njump nowhere always\nend";
+    /// let parsed = Lexer::<Statement>::new(src).parse_all();
+    ///
+    /// assert_eq!(parsed.statements.len(), 2);
+    /// assert_eq!(parsed.errors.len(), 2);
+    /// ```
+    #[must_use]
+    pub fn parse_all(self) -> ParsedSource<'a, T> {
+        let mut parsed = ParsedSource {
+            statements: Vec::new(),
+            errors: Vec::new(),
+            jump_labels: self.jump_labels.clone(),
+        };
+
+        for statement in self.spanned() {
+            match statement {
+                Ok(x) => parsed.statements.push(x),
+                Err(x) => parsed.errors.push(x),
+            }
+        }
+
+        parsed
+    }
+}
+
+/// The result of parsing a whole source with [`Lexer::parse_all`]
+#[derive(Debug, PartialEq)]
+pub struct ParsedSource<'a, T> {
+    /// Every statement that parsed successfully, in source order
+    pub statements: Vec<SpannedStatement<'a, T>>,
+    /// Every error, in source order
+    pub errors: Vec<ParseError<'a>>,
+    /// The jump labels in the source, along with the index of the statement each one points to
+    pub jump_labels: HashMap<&'a str, usize>,
+}
+
+impl<T> ParsedSource<'_, T> {
+    /// Checks if there were no errors
+    #[must_use]
+    pub fn is_ok(&self) -> bool {
+        self.errors.is_empty()
+    }
+}
+
+impl<'a, T: StatementType<'a>> Iterator for Lexer<'a, T> {
+    type Item = Result<T, ParseError<'a>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next_spanned().map(|x| x.map(|x| x.statement))
+    }
 }
@@ -1,9 +1,11 @@
 pub mod args;
 pub mod errs;
 pub mod lexer;
+pub mod span;
 pub mod statements;
 
 pub use errs::ParseError;
-pub use lexer::Lexer;
+pub use lexer::{Lexer, ParsedSource};
+pub use span::{Span, Spanned, SpannedStatement};
 pub use statements::ParseError as StatementParseErr;
 pub use statements::Statement;
@@ -0,0 +1,73 @@
+//! Source positions for statements and tokens.
+
+use std::fmt;
+
+/// A region of the source. All positions are 0-based.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Span {
+    /// The byte offset of the start of the span
+    pub start: usize,
+    /// The byte offset just after the end of the span
+    pub end: usize,
+    /// The line the span starts on
+    pub line: usize,
+    /// The column (in characters) the span starts on
+    pub column: usize,
+}
+
+impl Span {
+    /// Gets the smallest span covering both `self` and `other`. The line and column are taken from
+    /// whichever span starts first.
+    #[must_use]
+    pub fn to(self, other: Span) -> Span {
+        let first = if self.start <= other.start {
+            self
+        } else {
+            other
+        };
+
+        Span {
+            start: first.start,
+            end: self.end.max(other.end),
+            line: first.line,
+            column: first.column,
+        }
+    }
+}
+
+impl fmt::Display for Span {
+    /// Prints the span as `line:column`, 1-based like most editors.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}", self.line + 1, self.column + 1)
+    }
+}
+
+/// A value with the span it was parsed from
+#[derive(Debug, PartialEq, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Spanned<T> {
+    /// The value
+    pub value: T,
+    /// Where the value is in the source
+    pub span: Span,
+}
+
+/// A statement, along with the spans of it and all of its tokens. This is created by
+/// [`Lexer::spanned`](crate::parser::lexer::Lexer::spanned).
+///
+/// `tokens` are in source order, which isn't always the order of the statement's fields: inputs
+/// always come first in a statement, but instructions like `sensor` and `op` write their outputs
+/// before their inputs. The span of an operand is the entry of `tokens` at its position in the
+/// instruction as it's written.
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SpannedStatement<'a, T> {
+    /// The statement
+    pub statement: T,
+    /// The span of the whole statement
+    pub span: Span,
+    /// Every token in the statement (including the instruction name)
+    #[cfg_attr(feature = "serde", serde(borrow))]
+    pub tokens: Vec<Spanned<&'a str>>,
+}
@@ -15,7 +15,7 @@ macro_rules! gen_match_l {
     (
         io
         $($name:literal),*
-        $($i:ident),* -> $($o:ident),*
+        $($o:ident),* -> $($i:ident),*
     ) => { [$($name),*, $($i,)* $($o,)* ..] };
 }
 
@@ -43,8 +43,9 @@ macro_rules! gen_enum {
         pub enum $enum<'a> {
             /// A jump statement
             Jump {
-                /// The index to jump to
-                index: usize,
+                /// The statement to jump to
+                #[cfg_attr(feature = "serde", serde(borrow))]
+                target: super::JumpTarget<'a>,
                 /// The condition
                 cond: ConditionOp,
                 /// The condition LHS
@@ -67,6 +68,13 @@ macro_rules! gen_enum {
                 /// Option when false
                 false_option: Argument<'a>
             },
+            /// An unknown or invalid instruction, kept as it was written. This is only created
+            /// when [`ParseOptions::keep_unknown`](super::ParseOptions::keep_unknown) is set.
+            Unknown {
+                /// The tokens of the instruction
+                #[cfg_attr(feature = "serde", serde(borrow))]
+                tokens: Vec<&'a str>
+            },
 
             $(
                 // There just isn't much of a point in adding doc support
@@ -110,23 +118,61 @@ macro_rules! impl_statement {
     ) => {
         impl<'a> $enum<'a> {
             /// Gets the number of operands that can be passed to a statement.
-            ///
-            /// # Panics
-            ///
-            /// This function panics if a jump or select statement that is less than 3 items long is
-            /// passed in that has less than 3 elements. This isn't validated since it couldn't
-            /// possibly be matched.
             fn operand_count(tokens: &[&'a str]) -> usize {
+                let always = tokens.get(2) == Some(&"always");
+
                 match tokens {
                     // These are based on the longest possible invocation
-                    ["jump", ..]   => if tokens[2] == "always" { 3 } else { 5 },
-                    ["select", ..] => if tokens[2] == "always" { 5 } else { 7 },
+                    ["jump", ..]   => if always { 3 } else { 5 },
+                    ["select", ..] => if always { 5 } else { 7 },
+                    $(
+                        [$($name),*, ..] => { count_tts!($($name)* $($i)* $($o)*) }
+                    )*
+                    _ => 0
+                }
+            }
+
+            /// Gets the number of tokens naming the instruction (e.g. 2 for `op add`).
+            fn name_count(tokens: &[&'a str]) -> usize {
+                match tokens {
+                    ["jump", ..] | ["select", ..] => 1,
                     $(
-                        [$($name),*, ..] => { count_tts!($($name)* $($i:ident)* $($o:ident)*) }
+                        [$($name),*, ..] => { count_tts!($($name)*) }
                     )*
                     _ => 0
                 }
             }
+
+            /// Gets the largest number of tokens a statement can have in strict mode. This is
+            /// usually the same as [`Self::operand_count`], but the game always writes out
+            /// some instructions with a fixed number of operands, padding them with unused ones.
+            fn max_operand_count(tokens: &[&'a str]) -> usize {
+                let game_width = tokens.first().map_or(0, |x| super::game_token_count(x));
+                game_width.max(Self::operand_count(tokens))
+            }
+
+            /// Checks that the right number of operands was passed in.
+            fn check_arity(tokens: &[&'a str]) -> Result<(), super::ParseError<'a>> {
+                let names = Self::name_count(tokens);
+                let (min, max) = (Self::operand_count(tokens), Self::max_operand_count(tokens));
+
+                // Unknown instructions are reported elsewhere
+                if names == 0 {
+                    Ok(())
+                } else if tokens.len() < min {
+                    Err(super::ParseError::TooFewOperands {
+                        expected: min - names,
+                        actual: tokens.len() - names
+                    })
+                } else if tokens.len() > max {
+                    Err(super::ParseError::TooManyOperands {
+                        expected: max - names,
+                        actual: tokens.len() - names
+                    })
+                } else {
+                    Ok(())
+                }
+            }
         }
 
         impl<'a> StatementType<'a> for $enum<'a> {
@@ -135,56 +181,36 @@ macro_rules! impl_statement {
             /// # Errors
             ///
             /// If the jump label a `jump` statement points to isn't found in the jump_labels
-            /// parameter or an invalid statement is passed in, an error variant is returned.
+            /// parameter or an invalid statement is passed in, an error variant is returned. In
+            /// strict mode, passing the wrong number of operands is also an error.
             fn try_parse(
                 tokens: &[&'a str],
-                jump_labels: &std::collections::HashMap<&'a str, usize>
+                jump_labels: &std::collections::HashMap<&'a str, usize>,
+                options: super::ParseOptions
             ) -> Result<Self, super::ParseError<'a>> {
+                if options.arity == super::Arity::Strict {
+                    Self::check_arity(tokens)?;
+                }
+
                 let mut padded_tokens = tokens.to_vec();
                 padded_tokens.resize(Self::operand_count(tokens), "0");
 
                 match padded_tokens.as_slice() {
                     ["jump", index, cond_str, lhs, rhs, ..] if ConditionOp::try_from(*cond_str).is_ok() => {
-                        if let Ok(index) = index.parse() {
-                            Ok(Self::Jump {
-                                index,
-                                cond: ConditionOp::try_from(*cond_str).unwrap(),
-                                lhs: Some(Argument::from(*lhs)),
-                                rhs: Some(Argument::from(*rhs))
-                            })
-                        }
-                        else {
-                            Ok(Self::Jump {
-                                index: jump_labels
-                                    .get(*index)
-                                    .ok_or(super::ParseError::MissingJumpLabel(index))?
-                                    .clone(),
-                                cond: ConditionOp::try_from(*cond_str).unwrap(),
-                                lhs: Some(Argument::from(*lhs)),
-                                rhs: Some(Argument::from(*rhs))
-                            })
-                        }
+                        Ok(Self::Jump {
+                            target: super::JumpTarget::resolve(index, jump_labels)?,
+                            cond: ConditionOp::try_from(*cond_str).unwrap(),
+                            lhs: Some(Argument::from(*lhs)),
+                            rhs: Some(Argument::from(*rhs))
+                        })
                     },
                     ["jump", index, "always", ..] => {
-                        if let Ok(index) = index.parse() {
-                            Ok(Self::Jump {
-                                index,
-                                cond: ConditionOp::Always,
-                                lhs: None,
-                                rhs: None
-                            })
-                        }
-                        else {
-                            Ok(Self::Jump {
-                                index: jump_labels
-                                    .get(*index)
-                                    .ok_or(super::ParseError::MissingJumpLabel(index))?
-                                    .clone(),
-                                cond: ConditionOp::Always,
-                                lhs: None,
-                                rhs: None
-                            })
-                        }
+                        Ok(Self::Jump {
+                            target: super::JumpTarget::resolve(index, jump_labels)?,
+                            cond: ConditionOp::Always,
+                            lhs: None,
+                            rhs: None
+                        })
                     },
                     ["select", result, cond_str, lhs, rhs, true_option, false_option, ..] if ConditionOp::try_from(*cond_str).is_ok() => {
                         Ok(Self::Select {
@@ -211,9 +237,37 @@ macro_rules! impl_statement {
                             /*if gen_match_guard!($($o)*)*/
                         => Ok(gen_match_result!($enum $ident $($i),* -> $($o),*)),
                     )*
+                    _ if options.keep_unknown => Ok(Self::Unknown { tokens: tokens.to_vec() }),
                     _ => Err(super::ParseError::InvalidInstruction(tokens.to_vec()))
                 }
             }
+
+            fn jump_target(&self) -> Option<&super::JumpTarget<'a>> {
+                match self {
+                    Self::Jump { target, .. } => Some(target),
+                    _ => None
+                }
+            }
+
+            fn jump_target_mut(&mut self) -> Option<&mut super::JumpTarget<'a>> {
+                match self {
+                    Self::Jump { target, .. } => Some(target),
+                    _ => None
+                }
+            }
+
+            fn fmt_with_label(
+                &self,
+                f: &mut std::fmt::Formatter<'_>,
+                label: &str
+            ) -> std::fmt::Result {
+                match self {
+                    Self::Jump { cond, lhs: Some(lhs), rhs: Some(rhs), .. } =>
+                        write!(f, "jump {} {} {} {}", label, cond, lhs, rhs),
+                    Self::Jump { cond, .. } => write!(f, "jump {} {}", label, cond),
+                    _ => std::fmt::Display::fmt(self, f)
+                }
+            }
         }
     }
 }
@@ -281,15 +335,17 @@ macro_rules! gen_statements {
         impl std::fmt::Display for $enum<'_> {
             fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
                 match self {
-                    Self::Jump { index, cond, lhs: None, rhs: None } =>
-                        write!(f, "jump {} {}", index, cond),
-                    Self::Jump { index, cond, lhs: Some(lhs), rhs: Some(rhs) } =>
-                        write!(f, "jump {} {} {} {}", index, cond, lhs, rhs),
+                    Self::Jump { target, cond, lhs: None, rhs: None } =>
+                        write!(f, "jump {} {}", target, cond),
+                    Self::Jump { target, cond, lhs: Some(lhs), rhs: Some(rhs) } =>
+                        write!(f, "jump {} {} {} {}", target, cond, lhs, rhs),
                     Self::Select { result, cond, lhs: None, rhs: None, true_option, false_option } =>
                         write!(f, "select {} {} {} {}", result, cond, true_option, false_option),
                     Self::Select { result, cond, lhs: Some(lhs), rhs: Some(rhs), true_option, false_option } =>
                         write!(f, "select {} {} {} {} {} {}", result, cond, lhs, rhs, true_option, false_option),
 
+                    Self::Unknown { tokens } => f.write_str(&tokens.join(" ")),
+
                     // Other combinations should be impossible
                     Self::Jump {..} | Self::Select {..} => unreachable!(),
                     $(
@@ -305,15 +361,17 @@ macro_rules! gen_statements {
         impl std::fmt::Display for $wproc_enum<'_> {
             fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
                 match self {
-                    Self::Jump { index, cond, lhs: None, rhs: None } =>
-                        write!(f, "jump {} {}", index, cond),
-                    Self::Jump { index, cond, lhs: Some(lhs), rhs: Some(rhs) } =>
-                        write!(f, "jump {} {} {} {}", index, cond, lhs, rhs),
+                    Self::Jump { target, cond, lhs: None, rhs: None } =>
+                        write!(f, "jump {} {}", target, cond),
+                    Self::Jump { target, cond, lhs: Some(lhs), rhs: Some(rhs) } =>
+                        write!(f, "jump {} {} {} {}", target, cond, lhs, rhs),
                     Self::Select { result, cond, lhs: None, rhs: None, true_option, false_option } =>
                         write!(f, "select {} {} {} {}", result, cond, true_option, false_option),
                     Self::Select { result, cond, lhs: Some(lhs), rhs: Some(rhs), true_option, false_option } =>
                         write!(f, "select {} {} {} {} {} {}", result, cond, lhs, rhs, true_option, false_option),
 
+                    Self::Unknown { tokens } => f.write_str(&tokens.join(" ")),
+
                     // Other combinations should be impossible
                     Self::Jump {..} | Self::Select {..} => unreachable!(),
                     $(
@@ -329,10 +387,43 @@ macro_rules! gen_statements {
                 }
             }
         }
+
+        impl<'a> From<$enum<'a>> for $wproc_enum<'a> {
+            fn from(statement: $enum<'a>) -> Self {
+                match statement {
+                    $enum::Jump { target, cond, lhs, rhs } => Self::Jump { target, cond, lhs, rhs },
+                    $enum::Select { result, cond, lhs, rhs, true_option, false_option } =>
+                        Self::Select { result, cond, lhs, rhs, true_option, false_option },
+                    $enum::Unknown { tokens } => Self::Unknown { tokens },
+                    $(
+                        $enum::$ident {$($i),* $(,$o)*} => Self::$ident {$($i),* $(,$o)*},
+                    )*
+                }
+            }
+        }
+
+        impl<'a> TryFrom<$wproc_enum<'a>> for $enum<'a> {
+            type Error = $wproc_enum<'a>;
+
+            /// Converts a world processor statement into a normal one, giving it back if only
+            /// world processors can run it
+            fn try_from(statement: $wproc_enum<'a>) -> Result<Self, Self::Error> {
+                match statement {
+                    $wproc_enum::Jump { target, cond, lhs, rhs } => Ok(Self::Jump { target, cond, lhs, rhs }),
+                    $wproc_enum::Select { result, cond, lhs, rhs, true_option, false_option } =>
+                        Ok(Self::Select { result, cond, lhs, rhs, true_option, false_option }),
+                    $wproc_enum::Unknown { tokens } => Ok(Self::Unknown { tokens }),
+                    $(
+                        $wproc_enum::$ident {$($i),* $(,$o)*} => Ok(Self::$ident {$($i),* $(,$o)*}),
+                    )*
+                    x => Err(x),
+                }
+            }
+        }
     }
 }}
 use std::collections::HashMap;
-use std::fmt::Display;
+use std::fmt::{self, Display};
 
 /// An error found when parsing a statement
 #[derive(Debug, PartialEq)]
@@ -341,6 +432,120 @@ pub enum ParseError<'s> {
     MissingJumpLabel(&'s str),
     /// Invalid instruction
     InvalidInstruction(Vec<&'s str>),
+    /// Not enough operands were passed in (only in strict mode)
+    TooFewOperands {
+        /// The minimum number of operands
+        expected: usize,
+        /// The number of operands passed in
+        actual: usize,
+    },
+    /// Too many operands were passed in (only in strict mode)
+    TooManyOperands {
+        /// The maximum number of operands
+        expected: usize,
+        /// The number of operands passed in
+        actual: usize,
+    },
+}
+
+/// Where a `jump` statement goes to
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct JumpTarget<'a> {
+    /// The index of the statement to jump to
+    pub index: usize,
+    /// The label used in the source, if the jump used one instead of an index
+    pub label: Option<&'a str>,
+}
+
+impl<'a> JumpTarget<'a> {
+    /// Creates a jump target from an index, without a label
+    #[must_use]
+    pub fn new(index: usize) -> Self {
+        Self { index, label: None }
+    }
+
+    /// Creates a jump target from a resolved label
+    #[must_use]
+    pub fn labelled(index: usize, label: &'a str) -> Self {
+        Self {
+            index,
+            label: Some(label),
+        }
+    }
+
+    /// Resolves the target of a jump from its token, which is either an index or a label.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ParseError::MissingJumpLabel`] if the token isn't an index and isn't a key in
+    /// `jump_labels`.
+    pub fn resolve(
+        token: &'a str,
+        jump_labels: &HashMap<&'a str, usize>,
+    ) -> Result<Self, ParseError<'a>> {
+        if let Ok(index) = token.parse() {
+            Ok(Self::new(index))
+        } else {
+            jump_labels
+                .get(token)
+                .map(|index| Self::labelled(*index, token))
+                .ok_or(ParseError::MissingJumpLabel(token))
+        }
+    }
+}
+
+impl From<usize> for JumpTarget<'_> {
+    fn from(index: usize) -> Self {
+        Self::new(index)
+    }
+}
+
+impl fmt::Display for JumpTarget<'_> {
+    /// Prints the index. Labels aren't printed, since a single statement can't define them.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.index)
+    }
+}
+
+/// Gets the number of tokens the game writes out for an instruction, including its name. Unused
+/// operands are padded (usually with `0`), so this is the same for every variant of an
+/// instruction. Returns 0 for unknown instructions.
+fn game_token_count(instruction: &str) -> usize {
+    match instruction {
+        "end" | "stop" | "noop" => 1,
+        "print" | "printchar" | "format" | "printflush" | "drawflush" | "wait" | "ubind"
+        | "setrate" | "sync" | "localeprint" => 2,
+        "set" | "getlink" | "getflag" | "setflag" | "weathersense" | "weatherset" => 3,
+        "read" | "write" | "sensor" | "lookup" | "setprop" | "message" | "spawnwave" => 4,
+        "jump" | "op" | "getblock" | "status" => 5,
+        "packcolor" | "unpackcolor" | "cutscene" | "fetch" | "makemarker" | "setmarker" => 6,
+        "select" | "control" | "ucontrol" | "effect" | "setblock" | "setrule" | "spawn" => 7,
+        "draw" | "radar" | "uradar" => 8,
+        "ulocate" | "playsound" => 9,
+        "explosion" => 10,
+        _ => 0,
+    }
+}
+
+/// How to handle statements with the wrong number of operands
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
+pub enum Arity {
+    /// Pad missing operands with `0` and ignore extra ones, like the game does
+    #[default]
+    Lenient,
+    /// Return an error for missing or extra operands
+    Strict,
+}
+
+/// Options for parsing statements
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
+pub struct ParseOptions {
+    /// How to handle statements with the wrong number of operands
+    pub arity: Arity,
+    /// Whether to keep unknown instructions as `Unknown` statements instead of returning an error.
+    /// This is how the game handles them.
+    pub keep_unknown: bool,
 }
 
 /// Trait for anything that can be used as a statement
@@ -349,7 +554,18 @@ pub trait StatementType<'a>: Display + Sized {
     fn try_parse(
         tokens: &[&'a str],
         jump_labels: &HashMap<&'a str, usize>,
+        options: ParseOptions,
     ) -> Result<Self, ParseError<'a>>;
+
+    /// Gets where this statement jumps to, if it's a jump
+    fn jump_target(&self) -> Option<&JumpTarget<'a>>;
+
+    /// Gets a mutable reference to where this statement jumps to, if it's a jump
+    fn jump_target_mut(&mut self) -> Option<&mut JumpTarget<'a>>;
+
+    /// Writes the statement the same way as [`Display`], except that jumps use `label` as their
+    /// target instead of the index.
+    fn fmt_with_label(&self, f: &mut fmt::Formatter<'_>, label: &str) -> fmt::Result;
 }
 
 gen_statements! {
@@ -364,8 +580,8 @@ gen_statements! {
         Wait: "wait" (io: time ->)
 
         GetLink: "getlink" (oi: index -> result)
-        Radar:   "radar"   (io: m1, m2, m3, sort, block -> result)
-        Sensor:  "sensor"  (oi: item -> result)
+        Radar:   "radar"   (io: m1, m2, m3, sort, block, order -> result)
+        Sensor:  "sensor"  (oi: block, property -> result)
 
         Read:  "read"  (oi: cell, index -> result)
         Write: "write" (io: value, cell, index ->)
@@ -386,8 +602,8 @@ gen_statements! {
 
         DrawRect:     "draw" "rect"     (oi: x, y, w, h ->)
         DrawLineRect: "draw" "lineRect" (oi: x, y, w, h ->)
-        DrawPoly:     "draw" "poly"     (oi: x, y, w, h ->)
-        DrawLinePoly: "draw" "linePoly" (oi: x, y, w, h ->)
+        DrawPoly:     "draw" "poly"     (oi: x, y, w, h, rotation ->)
+        DrawLinePoly: "draw" "linePoly" (oi: x, y, w, h, rotation ->)
         DrawLine:     "draw" "line"     (oi: x, y, x2, y2 ->)
 
         DrawTri:   "draw" "triangle" (io: x1, y1, x2, y2, x3, y3 ->)
@@ -398,9 +614,9 @@ gen_statements! {
         DrawRotate:     "draw" "rotate"    (oi: angle ->)
         DrawScale:      "draw" "scale"     (oi: x, y ->)
 
-        ControlEnabled: "control" "enabled" (oi: enabled ->)
-        ControlConfig:  "control" "config"  (oi: config ->)
-        ControlColour:  "control" "color"   (oi: colour ->)
+        ControlEnabled: "control" "enabled" (oi: block, enabled ->)
+        ControlConfig:  "control" "config"  (oi: block, config ->)
+        ControlColour:  "control" "color"   (oi: block, colour ->)
         ControlShoot:   "control" "shoot"   (oi: block, x, y, shoot ->)
         ControlShootP:  "control" "shootp"  (oi: block, unit, shoot ->)
 
@@ -454,8 +670,8 @@ gen_statements! {
         OpRound: "op" "round" (oi: x -> result)
         OpSqrt:  "op" "sqrt"  (oi: x -> result)
 
-        OpLog:   "op" "log"   (oi: a, b -> result)
-        OpLogN:  "op" "logn"  (oi: x -> result)
+        OpLog:   "op" "log"   (oi: x -> result)
+        OpLogN:  "op" "logn"  (oi: a, b -> result)
         OpLog10: "op" "log10" (oi: x -> result)
 
         OpSin:   "op" "sin"  (oi: x -> result)
@@ -466,8 +682,8 @@ gen_statements! {
         OpATan:  "op" "atan" (oi: x -> result)
 
         UBind:   "ubind"   (io: unit_type ->)
-        ULocate: "ulocate" (io: find, group, enemy, outx, outy -> found, building)
-        URadar:  "uradar"  (io: m1, m2, m3, sort, block -> result)
+        ULocate: "ulocate" (io: find, group, enemy, ore -> outx, outy, found, building)
+        URadar:  "uradar"  (io: m1, m2, m3, sort, block, order -> result)
 
         UCIdle:         "ucontrol" "idle"         (oi: ->)
         UCStop:         "ucontrol" "stop"         (oi: ->)
@@ -480,12 +696,12 @@ gen_statements! {
         UCPathfind:     "ucontrol" "pathfind"     (oi: x, y ->)
         UCAutoPathfind: "ucontrol" "autoPathfind" (oi: ->)
         UCApproach:     "ucontrol" "approach"     (oi: x, y, radius ->)
-        UCWithin:       "ucontrol" "within"       (oi: x, y, radius -> result)
+        UCWithin:       "ucontrol" "within"       (io: x, y, radius -> result)
         UCBoost:        "ucontrol" "boost"        (oi: boost ->)
         UCMine:         "ucontrol" "mine"         (oi: x, y ->)
         UCTarget:       "ucontrol" "target"       (oi: x, y, shoot ->)
         UCTargetP:      "ucontrol" "targetp"      (oi: unit, shoot ->)
-        UCItemTake:     "ucontrol" "itemTake"     (oi: x, y, radius ->)
+        UCItemTake:     "ucontrol" "itemTake"     (oi: from, item, amount ->)
         UCItemDrop:     "ucontrol" "itemDrop"     (oi: to, amount ->)
         UCPayloadTake:  "ucontrol" "payTake"      (oi: units ->)
         UCPayloadDrop:  "ucontrol" "payDrop"      (oi: ->)
@@ -493,10 +709,13 @@ gen_statements! {
     ---
 
     wproc:
-        GetBlock:     "getblock"         (oi: x, y -> result)
-        SetBOre:      "setblock" "ore"   (oi: x, y, to ->)
-        SetBFloor:    "setblock" "floor" (oi: x, y, to ->)
-        SetBBlock:    "setblock" "block" (oi: x, y, to, team, rotation ->)
+        GetBFloor:    "getblock" "floor"    (oi: x, y -> result)
+        GetBOre:      "getblock" "ore"      (oi: x, y -> result)
+        GetBBlock:    "getblock" "block"    (oi: x, y -> result)
+        GetBBuilding: "getblock" "building" (oi: x, y -> result)
+        SetBOre:      "setblock" "ore"      (oi: to, x, y ->)
+        SetBFloor:    "setblock" "floor"    (oi: to, x, y ->)
+        SetBBlock:    "setblock" "block"    (oi: to, x, y, team, rotation ->)
         SetProp:      "setprop"          (oi: prop, block, value ->)
         ShowMessage:  "message"          (oi: msg_type, duration, success ->)
         WeatherSense: "weathersense"     (oi: weather -> result)
@@ -512,21 +731,21 @@ gen_statements! {
         CutscenePan:  "cutscene" "pan"  (oi: x, y, speed ->)
 
         FetchUnit:        "fetch" "unit"        (oi: team, number, unit -> result)
-        FetchUnitCount:   "fetch" "unitCount"   (oi: team, unit -> result)
+        FetchUnitCount:   "fetch" "unitCount"   (oi: team, _1, unit -> result)
         FetchPlayer:      "fetch" "player"      (oi: team, number -> result)
         FetchPlayerCount: "fetch" "playerCount" (oi: team -> result)
         FetchCore:        "fetch" "core"        (oi: team, number -> result)
         FetchCoreCount:   "fetch" "coreCount"   (oi: team -> result)
         FetchBuild:       "fetch" "build"       (oi: team, number, block -> result)
-        FetchBuildCount:  "fetch" "buildCount"  (oi: team, block -> result)
+        FetchBuildCount:  "fetch" "buildCount"  (oi: team, _1, block -> result)
 
-        PlaySoundPositional: "playsound" "false" (oi: sound, volume, pitch, x, y, _1, limit ->)
-        PlaySoundGlobal:     "playsound" "true"  (oi: sound, volume, pitch, _1, _2, pan, limit ->)
+        PlaySoundPositional: "playsound" "true"  (oi: sound, volume, pitch, _1, x, y, limit ->)
+        PlaySoundGlobal:     "playsound" "false" (oi: sound, volume, pitch, pan, _1, _2, limit ->)
         Explosion:           "explosion" (oi: team, x, y, radius, damage, air, ground, pierce, effect ->)
 
         SpawnUnit:    "spawn"        (io: unit_type, x, y, rotation, team -> result)
         ApplyStatus:  "status"       (oi: _padding, wet, unit, duration ->)
-        SpawnWave:    "spawnwave"    (oi: natural, x, y ->)
+        SpawnWave:    "spawnwave"    (oi: x, y, natural ->)
 
         SetRCurrentWaveTime:      "setrule" "currentWaveTime"      (oi: v ->)
         SetRWaveTimer:            "setrule" "waveTimer"            (oi: v ->)
@@ -546,57 +765,57 @@ gen_statements! {
         SetRBan:                  "setrule" "ban"                  (oi: index ->)
         SetRUnban:                "setrule" "unban"                (oi: index ->)
 
-        SetRBuildSpeed:     "setrule" "buildSpeed"     (oi: team, v ->)
-        SetRUnitHealth:     "setrule" "unitHealth"     (oi: team, v ->)
-        SetRUnitBuildSpeed: "setrule" "unitBuildSpeed" (oi: team, v ->)
-        SetRUnitMineSpeed:  "setrule" "unitMineSpeed"  (oi: team, v ->)
-        SetRUnitCost:       "setrule" "unitCost"       (oi: team, v ->)
-        SetRUnitDamage:     "setrule" "unitDamage"     (oi: team, v ->)
-        SetRBlockHealth:    "setrule" "blockHealth"    (oi: team, v ->)
-        SetRBlockDamage:    "setrule" "blockDamage"    (oi: team, v ->)
-        SetRRtsMinWeight:   "setrule" "rtsMinWeight"   (oi: team, v ->)
-        SetRRtsMinSquad:    "setrule" "rtsMinSquad"    (oi: team, v ->)
-        SetRMapArea:        "setrule" "mapArea"        (oi: x, y, w, h ->)
+        SetRBuildSpeed:     "setrule" "buildSpeed"     (oi: v, team ->)
+        SetRUnitHealth:     "setrule" "unitHealth"     (oi: v, team ->)
+        SetRUnitBuildSpeed: "setrule" "unitBuildSpeed" (oi: v, team ->)
+        SetRUnitMineSpeed:  "setrule" "unitMineSpeed"  (oi: v, team ->)
+        SetRUnitCost:       "setrule" "unitCost"       (oi: v, team ->)
+        SetRUnitDamage:     "setrule" "unitDamage"     (oi: v, team ->)
+        SetRBlockHealth:    "setrule" "blockHealth"    (oi: v, team ->)
+        SetRBlockDamage:    "setrule" "blockDamage"    (oi: v, team ->)
+        SetRRtsMinWeight:   "setrule" "rtsMinWeight"   (oi: v, team ->)
+        SetRRtsMinSquad:    "setrule" "rtsMinSquad"    (oi: v, team ->)
+        SetRMapArea:        "setrule" "mapArea"        (oi: _1, x, y, w, h ->)
 
         EffectWarn:        "effect" "warn"            (oi: x, y ->)
         EffectCross:       "effect" "cross"           (oi: x, y ->)
         EffectSpawn:       "effect" "spawn"           (oi: x, y ->)
-        EffectTrail:       "effect" "trail"           (oi: x, y, colour, size ->)
-        EffectBreakProp:   "effect" "breakProp"       (oi: x, y, colour, size ->)
-        EffectSmokeCloud:  "effect" "smokeCloud"      (oi: x, y, colour ->)
-        EffectVapour:      "effect" "vapor"           (oi: x, y, colour ->)
-        EffectHit:         "effect" "hit"             (oi: x, y, colour ->)
-        EffectHitSquare:   "effect" "hitSquare"       (oi: x, y, colour ->)
-        EffectWave:        "effect" "wave"            (oi: x, y, colour, size ->)
+        EffectTrail:       "effect" "trail"           (oi: x, y, size, colour ->)
+        EffectBreakProp:   "effect" "breakProp"       (oi: x, y, size, colour ->)
+        EffectSmokeCloud:  "effect" "smokeCloud"      (oi: x, y, _1, colour ->)
+        EffectVapour:      "effect" "vapor"           (oi: x, y, _1, colour ->)
+        EffectHit:         "effect" "hit"             (oi: x, y, _1, colour ->)
+        EffectHitSquare:   "effect" "hitSquare"       (oi: x, y, _1, colour ->)
+        EffectWave:        "effect" "wave"            (oi: x, y, size, colour ->)
         EffectBubble:      "effect" "bubble"          (oi: x, y ->)
-        EffectSmokePuff:   "effect" "smokePuff"       (oi: x, y, colour ->)
+        EffectSmokePuff:   "effect" "smokePuff"       (oi: x, y, _1, colour ->)
 
-        EffectBlockFall:   "effect" "blockFall"       (oi: x, y, data ->)
+        EffectBlockFall:   "effect" "blockFall"       (oi: x, y, _1, _2, data ->)
         EffectPlaceBlock:  "effect" "placeBlock"      (oi: x, y, size ->)
         EffectPlaceBlockS: "effect" "placeBlockSpark" (oi: x, y, size ->)
         EffectBreakBlock:  "effect" "breakBlock"      (oi: x, y, size ->)
-        EffectLightBlock:  "effect" "lightBlock"      (oi: x, y, colour, size ->)
+        EffectLightBlock:  "effect" "lightBlock"      (oi: x, y, size, colour ->)
 
-        EffectShootBig:    "effect" "shootBig"        (oi: x, y, colour, rotation ->)
-        EffectShootSmall:  "effect" "shootSmall"      (oi: x, y, colour, rotation ->)
+        EffectShootBig:    "effect" "shootBig"        (oi: x, y, rotation, colour ->)
+        EffectShootSmall:  "effect" "shootSmall"      (oi: x, y, rotation, colour ->)
 
-        EffectSmokeSmall:     "effect" "smokeSmall"      (oi: x, y, rotation ->)
-        EffectSmokeBig:       "effect" "smokeBig"        (oi: x, y, rotation ->)
-        EffectSmokeColour:    "effect" "smokeColor"      (oi: x, y, colour, rotation ->)
-        EffectSmokeSquare:    "effect" "smokeSquare"     (oi: x, y, colour, rotation ->)
-        EffectSmokeSquareBig: "effect" "smokeSquareBig"  (oi: x, y, colour, rotation ->)
+        EffectSmokeSmall:     "effect" "smokeSmall"      (oi: x, y, _1, colour ->)
+        EffectSmokeBig:       "effect" "smokeBig"        (oi: x, y, _1, colour ->)
+        EffectSmokeColour:    "effect" "smokeColor"      (oi: x, y, rotation, colour ->)
+        EffectSmokeSquare:    "effect" "smokeSquare"     (oi: x, y, rotation, colour ->)
+        EffectSmokeSquareBig: "effect" "smokeSquareBig"  (oi: x, y, rotation, colour ->)
 
-        EffectSpark:          "effect" "spark"           (oi: x, y, rotation ->)
-        EffectSparkBig:       "effect" "sparkBig"        (oi: x, y, rotation ->)
-        EffectSparkShoot:     "effect" "sparkShoot"      (oi: x, y, colour, rotation ->)
-        EffectSparkShootBig:  "effect" "sparkShootBig"   (oi: x, y, colour, rotation ->)
+        EffectSpark:          "effect" "spark"           (oi: x, y, _1, colour ->)
+        EffectSparkBig:       "effect" "sparkBig"        (oi: x, y, _1, colour ->)
+        EffectSparkShoot:     "effect" "sparkShoot"      (oi: x, y, rotation, colour ->)
+        EffectSparkShootBig:  "effect" "sparkShootBig"   (oi: x, y, rotation, colour ->)
 
-        EffectDrill:          "effect" "drill"    (oi: x, y, colour ->)
-        EffectDrillBig:       "effect" "drillBig" (oi: x, y, colour ->)
+        EffectDrill:          "effect" "drill"    (oi: x, y, _1, colour ->)
+        EffectDrillBig:       "effect" "drillBig" (oi: x, y, _1, colour ->)
 
         EffectExplosion:      "effect" "explosion"      (oi: x, y, size ->)
-        EffectSparkExplosion: "effect" "sparkExplosion" (oi: x, y, colour ->)
-        EffectCrossExplosion: "effect" "crossExplosion" (oi: x, y, colour, size ->)
+        EffectSparkExplosion: "effect" "sparkExplosion" (oi: x, y, _1, colour ->)
+        EffectCrossExplosion: "effect" "crossExplosion" (oi: x, y, size, colour ->)
 
         MakeMarkerShapeText: "makemarker" "shapeText"  (oi: id, x, y, replace ->)
         MakeMarkerPoint:     "makemarker" "point"      (oi: id, x, y, replace ->)
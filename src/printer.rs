@@ -0,0 +1,147 @@
+//! Printing whole programs back out as mlog, with jump labels.
+
+use crate::parser::statements::StatementType;
+use std::{
+    borrow::Cow,
+    collections::{BTreeMap, HashMap},
+    fmt,
+};
+
+/// Prints a program with labels instead of jump indices, so that the output keeps working when
+/// statements are inserted or removed.
+///
+/// A label is written before every statement that is jumped to. Label names from the source are
+/// kept where they are known (either from the jumps themselves or from
+/// [`with_labels`](Self::with_labels)), and any other jump targets get a generated `labelN` name.
+///
+/// # Examples
+///
+/// ```
+/// # use mlog_parse::parser::lexer::Lexer;
+/// # use mlog_parse::parser::statements::Statement;
+/// # use mlog_parse::printer::LabelPrinter;
+/// const SRC: &str = r#"
+///     loop_start:
+///         op add i i 1
+///     jump loop_start lessThan i 5
+///     jump 0 always
+/// "#;
+///
+/// let statements: Vec<Statement> = Lexer::new(SRC).map(|x| x.unwrap()).collect();
+///
+/// assert_eq!(
+///     LabelPrinter::new(&statements).to_string(),
+///     "loop_start:\nop add i i 1\njump loop_start lessThan i 5\njump loop_start always\n"
+/// );
+/// ```
+pub struct LabelPrinter<'p, 'a, T> {
+    statements: &'p [T],
+    labels: HashMap<&'a str, usize>,
+}
+
+impl<'p, 'a, T: StatementType<'a>> LabelPrinter<'p, 'a, T> {
+    /// Creates a printer for a list of statements
+    #[must_use]
+    pub fn new(statements: &'p [T]) -> Self {
+        Self {
+            statements,
+            labels: HashMap::new(),
+        }
+    }
+
+    /// Adds labels to print even if nothing jumps to them (e.g. the ones from
+    /// [`Lexer::jump_labels`](crate::parser::lexer::Lexer::jump_labels)).
+    #[must_use]
+    pub fn with_labels(mut self, labels: &HashMap<&'a str, usize>) -> Self {
+        self.labels.extend(labels);
+        self
+    }
+
+    /// Gets the names of the labels at each index, generating names for jump targets without one.
+    fn label_table(&self) -> BTreeMap<usize, Vec<Cow<'a, str>>> {
+        let mut table: BTreeMap<usize, Vec<Cow<'a, str>>> = BTreeMap::new();
+        let mut add_label = |index, label: &'a str| {
+            let labels = table.entry(index).or_default();
+            if !labels.iter().any(|x| x == label) {
+                labels.push(Cow::Borrowed(label));
+            }
+        };
+
+        // Sorted so the output doesn't depend on the hash map's order
+        let mut known: Vec<_> = self.labels.iter().map(|(k, v)| (*v, *k)).collect();
+        known.sort_unstable();
+        for (index, label) in known {
+            add_label(index, label);
+        }
+
+        let targets: Vec<_> = self
+            .statements
+            .iter()
+            .filter_map(|x| x.jump_target())
+            .filter(|x| x.index <= self.statements.len())
+            .collect();
+
+        for target in &targets {
+            if let Some(label) = target.label {
+                add_label(target.index, label);
+            }
+        }
+
+        let mut unnamed: Vec<_> = targets
+            .iter()
+            .map(|x| x.index)
+            .filter(|x| !table.contains_key(x))
+            .collect();
+        unnamed.sort_unstable();
+        unnamed.dedup();
+
+        let mut counter = 0usize;
+        for index in unnamed {
+            let name = loop {
+                let name = format!("label{counter}");
+                counter += 1;
+                if !table.values().flatten().any(|x| *x == name) {
+                    break name;
+                }
+            };
+            table.insert(index, vec![Cow::Owned(name)]);
+        }
+
+        table
+    }
+}
+
+impl<'a, T: StatementType<'a>> fmt::Display for LabelPrinter<'_, 'a, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let table = self.label_table();
+        let write_labels = |f: &mut fmt::Formatter<'_>, index| {
+            for label in table.get(&index).into_iter().flatten() {
+                writeln!(f, "{label}:")?;
+            }
+            Ok(())
+        };
+
+        for (index, statement) in self.statements.iter().enumerate() {
+            write_labels(f, index)?;
+
+            match statement.jump_target() {
+                Some(target) => {
+                    let labels = table.get(&target.index);
+                    // Prefer the label the jump originally used
+                    let label = labels
+                        .and_then(|x| x.iter().find(|x| Some(x.as_ref()) == target.label))
+                        .or_else(|| labels.and_then(|x| x.first()));
+
+                    match label {
+                        Some(label) => statement.fmt_with_label(f, label)?,
+                        None => write!(f, "{statement}")?,
+                    }
+                }
+                None => write!(f, "{statement}")?,
+            }
+            writeln!(f)?;
+        }
+
+        write_labels(f, self.statements.len())
+    }
+}
@@ -0,0 +1,243 @@
+//! A container for whole programs that keeps jumps pointing at the right statements.
+
+use crate::parser::{errs::ParseError, lexer::Lexer, statements::StatementType};
+use crate::printer::LabelPrinter;
+use std::{
+    collections::HashMap,
+    fmt,
+    ops::{Bound, RangeBounds},
+};
+
+/// A program, made of statements and the jump labels pointing into them.
+///
+/// Statements can be inserted and removed without breaking jumps: every jump target and label is
+/// updated so that it keeps pointing at the same statement. Jumps to a removed statement end up
+/// pointing at whatever comes after it.
+///
+/// Jumps in statements passed to [`insert`](Self::insert) and [`splice`](Self::splice) are taken
+/// as indices into the resulting program, and aren't changed.
+///
+/// # Examples
+///
+/// ```
+/// # use mlog_parse::program::Program;
+/// # use mlog_parse::parser::statements::{Statement, JumpTarget};
+/// # use mlog_parse::parser::args::Argument;
+/// const SRC: &str = r#"
+///     set i 0
+///     loop:
+///         op add i i 1
+///     jump loop lessThan i 5
+/// "#;
+///
+/// let mut program: Program<Statement> = Program::parse(SRC).unwrap();
+/// program.insert(0, Statement::Print { text: Argument::String("start") });
+///
+/// assert_eq!(program.labels()["loop"], 2);
+/// assert!(matches!(
+///     program.statements()[3],
+///     Statement::Jump { target: JumpTarget { index: 2, .. }, .. }
+/// ));
+/// ```
+#[derive(Debug, PartialEq, Clone)]
+pub struct Program<'a, T> {
+    statements: Vec<T>,
+    labels: HashMap<&'a str, usize>,
+}
+
+impl<'a, T: StatementType<'a>> Default for Program<'a, T> {
+    fn default() -> Self {
+        Self::new(Vec::new(), HashMap::new())
+    }
+}
+
+impl<'a, T: StatementType<'a>> Program<'a, T> {
+    /// Creates a program from statements and the labels pointing into them
+    #[must_use]
+    pub fn new(statements: Vec<T>, labels: HashMap<&'a str, usize>) -> Self {
+        Self { statements, labels }
+    }
+
+    /// Parses a program.
+    ///
+    /// # Errors
+    ///
+    /// Returns every error found in the source if there were any.
+    pub fn parse(src: &'a str) -> Result<Self, Vec<ParseError<'a>>> {
+        Self::from_lexer(Lexer::new(src))
+    }
+
+    /// Parses a program from a lexer, which can have its own options set.
+    ///
+    /// # Errors
+    ///
+    /// Returns every error found in the source if there were any.
+    pub fn from_lexer(lexer: Lexer<'a, T>) -> Result<Self, Vec<ParseError<'a>>> {
+        let parsed = lexer.parse_all();
+
+        if parsed.is_ok() {
+            Ok(Self::new(
+                parsed.statements.into_iter().map(|x| x.statement).collect(),
+                parsed.jump_labels,
+            ))
+        } else {
+            Err(parsed.errors)
+        }
+    }
+
+    /// Gets the statements
+    #[must_use]
+    pub fn statements(&self) -> &[T] {
+        &self.statements
+    }
+
+    /// Gets a mutable reference to a statement
+    pub fn get_mut(&mut self, index: usize) -> Option<&mut T> {
+        self.statements.get_mut(index)
+    }
+
+    /// Gets the jump labels, along with the index of the statement each one points to
+    #[must_use]
+    pub fn labels(&self) -> &HashMap<&'a str, usize> {
+        &self.labels
+    }
+
+    /// Adds (or moves) a label
+    pub fn set_label(&mut self, label: &'a str, index: usize) {
+        self.labels.insert(label, index);
+    }
+
+    /// Gets the number of statements
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.statements.len()
+    }
+
+    /// Checks if there are no statements
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.statements.is_empty()
+    }
+
+    /// Takes the statements and labels out of the program
+    #[must_use]
+    pub fn into_parts(self) -> (Vec<T>, HashMap<&'a str, usize>) {
+        (self.statements, self.labels)
+    }
+
+    /// Changes every jump target and label with `f`.
+    fn map_targets(
+        statements: &mut [T],
+        labels: &mut HashMap<&'a str, usize>,
+        f: impl Fn(usize) -> usize,
+    ) {
+        for target in statements.iter_mut().filter_map(|x| x.jump_target_mut()) {
+            target.index = f(target.index);
+        }
+        for index in labels.values_mut() {
+            *index = f(*index);
+        }
+    }
+
+    /// Inserts a statement at `index`, moving everything after it along.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index > len`.
+    pub fn insert(&mut self, index: usize, statement: T) {
+        self.splice(index..index, [statement]);
+    }
+
+    /// Removes the statement at `index`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is out of bounds.
+    pub fn remove(&mut self, index: usize) -> T {
+        self.splice(index..=index, [])
+            .pop()
+            .expect("splice removes exactly one statement")
+    }
+
+    /// Replaces the statements in `range` with `replace_with`, returning the removed statements.
+    ///
+    /// Jumps to a replaced statement end up pointing at the start of the replacement.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the range is out of bounds, like [`Vec::splice`].
+    pub fn splice(
+        &mut self,
+        range: impl RangeBounds<usize>,
+        replace_with: impl IntoIterator<Item = T>,
+    ) -> Vec<T> {
+        let start = match range.start_bound() {
+            Bound::Included(x) => *x,
+            Bound::Excluded(x) => x + 1,
+            Bound::Unbounded => 0,
+        };
+        let end = match range.end_bound() {
+            Bound::Included(x) => x + 1,
+            Bound::Excluded(x) => *x,
+            Bound::Unbounded => self.statements.len(),
+        };
+
+        let replace_with: Vec<_> = replace_with.into_iter().collect();
+        let inserted = replace_with.len();
+
+        let removed: Vec<_> = self.statements.splice(start..end, []).collect();
+
+        Self::map_targets(&mut self.statements, &mut self.labels, |x| match x {
+            x if x < start => x,
+            x if x < end => start,
+            x => x - (end - start) + inserted,
+        });
+        self.statements.splice(start..start, replace_with);
+
+        removed
+    }
+
+    /// Appends another program to the end of this one. Jumps and labels in `other` are moved
+    /// along so they keep pointing at the same statements. Labels in `other` that already exist in
+    /// this program are dropped, and jumps in `other` that used them lose their label, so that
+    /// they're printed with a new one instead of jumping to the label in this program.
+    pub fn append(&mut self, other: Self) {
+        let offset = self.statements.len();
+        let (mut statements, mut labels) = other.into_parts();
+
+        Self::map_targets(&mut statements, &mut labels, |x| x + offset);
+
+        let taken = |label: &str| {
+            self.labels.contains_key(label)
+                || self
+                    .statements
+                    .iter()
+                    .filter_map(|x| x.jump_target())
+                    .any(|x| x.label == Some(label))
+        };
+        for target in statements.iter_mut().filter_map(|x| x.jump_target_mut()) {
+            if target.label.is_some_and(taken) {
+                target.label = None;
+            }
+        }
+        labels.retain(|label, _| !taken(label));
+
+        self.statements.extend(statements);
+        self.labels.extend(labels);
+    }
+
+    /// Adds a statement to the end of the program
+    pub fn push(&mut self, statement: T) {
+        let len = self.statements.len();
+        self.insert(len, statement);
+    }
+}
+
+impl<'a, T: StatementType<'a>> fmt::Display for Program<'a, T> {
+    /// Prints the program with labels, using [`LabelPrinter`].
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        LabelPrinter::new(&self.statements)
+            .with_labels(&self.labels)
+            .fmt(f)
+    }
+}
@@ -1,3 +1,7 @@
+mod panic_free;
 mod parser;
+mod printer;
+mod program;
 mod real_code;
 mod recursive_translation;
+mod vm;
@@ -0,0 +1,89 @@
+use crate::parser::{
+    args::Argument,
+    lexer::Lexer,
+    statements::{Arity, ParseOptions, Statement, WprocStatement},
+};
+use proptest::prelude::*;
+
+/// Tokens that are likely to hit edge cases in the parser
+const TOKENS: &[&str] = &[
+    "jump",
+    "select",
+    "op",
+    "add",
+    "draw",
+    "always",
+    "lessThan",
+    "set",
+    "label:",
+    "0x",
+    "-0x",
+    "0b",
+    "0xffffffffffffffffffff",
+    "1e",
+    "1e400",
+    ".",
+    "-",
+    "%",
+    "%[",
+    "%[]",
+    "%[red]",
+    "%ffff",
+    "%zzzzzz",
+    "\"",
+    "\"\"",
+    "@",
+    "#",
+    ";",
+    "\n",
+    " ",
+];
+
+fn parse_everything(src: &str) {
+    for (arity, keep_unknown) in [(Arity::Lenient, false), (Arity::Strict, true)] {
+        let options = ParseOptions {
+            arity,
+            keep_unknown,
+        };
+
+        let _ = Lexer::<Statement>::new(src)
+            .with_options(options)
+            .collect::<Vec<_>>();
+        let _ = Lexer::<WprocStatement>::new(src)
+            .with_options(options)
+            .spanned()
+            .collect::<Vec<_>>();
+    }
+}
+
+proptest! {
+    #[test]
+    fn lexer_any_string(src in any::<String>()) {
+        parse_everything(&src);
+    }
+
+    #[test]
+    fn lexer_token_soup(tokens in prop::collection::vec(prop::sample::select(TOKENS), 0..32)) {
+        parse_everything(&tokens.join(" "));
+        parse_everything(&tokens.concat());
+    }
+
+    #[test]
+    fn argument_any_string(src in any::<String>()) {
+        let _ = Argument::from(src.as_str());
+    }
+
+    #[test]
+    fn argument_literal_like(src in r#"[+-]{0,2}(0[xXbB])?[0-9a-fA-F.eE%\[\]"@+-]{0,24}"#) {
+        let _ = Argument::from(src.as_str());
+    }
+}
+
+#[test]
+fn known_panics() {
+    for src in [
+        "jump", "jump 0", "select", "select x", "set a %.", "set a %*", "set a 0x",
+    ] {
+        parse_everything(src);
+    }
+}
@@ -1,8 +1,12 @@
 use crate::parser::args::{Argument, ConditionOp, Rgba};
 use crate::parser::lexer::Lexer;
-use crate::parser::statements::Statement;
+use crate::parser::span::Span;
+use crate::parser::statements::{
+    Arity, JumpTarget, ParseOptions, Statement, StatementType, WprocStatement,
+};
 use crate::parser::{self, statements};
 use pretty_assertions::assert_eq;
+use std::collections::HashMap;
 
 #[test]
 fn single_input() {
@@ -71,6 +75,36 @@ fn semicolon() {
     )
 }
 
+#[test]
+fn quoted_strings() {
+    const SRC: &str = r##"
+        print "Mandelbrot Generator"
+        print "a; b" # comment
+        set a "# not a comment"; print a
+    "##;
+
+    let lexer: Lexer<Statement> = Lexer::new(SRC);
+
+    assert_eq!(
+        lexer.map(|x| x.unwrap()).collect::<Vec<_>>(),
+        vec![
+            Statement::Print {
+                text: Argument::String("Mandelbrot Generator")
+            },
+            Statement::Print {
+                text: Argument::String("a; b")
+            },
+            Statement::Set {
+                var: "a",
+                value: Argument::String("# not a comment")
+            },
+            Statement::Print {
+                text: Argument::Variable("a")
+            },
+        ]
+    )
+}
+
 #[test]
 fn operation() {
     const SRC: &str = r#"
@@ -131,7 +165,7 @@ fn ops_with_jump() {
         lexer.map(|x| x.unwrap()).collect::<Vec<_>>(),
         [
             Statement::Jump {
-                index: 2,
+                target: JumpTarget::new(2),
                 cond: ConditionOp::GreaterThan,
                 lhs: Some(Argument::Variable("a")),
                 rhs: Some(Argument::Number(2.0)),
@@ -153,7 +187,7 @@ fn ops_with_jump() {
                 b: Argument::Variable("a")
             },
             Statement::Jump {
-                index: 3,
+                target: JumpTarget::labelled(3, "jl2"),
                 cond: ConditionOp::LessThan,
                 lhs: Some(Argument::Variable("a")),
                 rhs: Some(Argument::Number(71.))
@@ -179,7 +213,7 @@ fn ops_with_jump() {
                 })
             },
             Statement::Jump {
-                index: 0,
+                target: JumpTarget::labelled(0, "jl1"),
                 cond: ConditionOp::Always,
                 lhs: None,
                 rhs: None
@@ -206,13 +240,23 @@ fn errs() {
         jump jlbl1 always
     "#;
 
+    let span_of = |text: &str, line| {
+        let start = SRC.find(text).unwrap();
+        Span {
+            start,
+            end: start + text.len(),
+            line,
+            column: start - SRC[..start].rfind('\n').unwrap() - 1,
+        }
+    };
+
     let lexer: Lexer<Statement> = Lexer::new(SRC);
 
     assert_eq!(
         lexer.collect::<Vec<_>>(),
         [
             Ok(Statement::Jump {
-                index: 2,
+                target: JumpTarget::new(2),
                 cond: ConditionOp::GreaterThan,
                 lhs: Some(Argument::Variable("a")),
                 rhs: Some(Argument::Number(2.0)),
@@ -234,13 +278,13 @@ fn errs() {
                 b: Argument::Variable("a")
             }),
             Ok(Statement::Jump {
-                index: 3,
+                target: JumpTarget::labelled(3, "jl2"),
                 cond: ConditionOp::LessThan,
                 lhs: Some(Argument::Variable("a")),
                 rhs: Some(Argument::Number(71.))
             }),
             Err(parser::ParseError::Statement {
-                line: 11,
+                span: span_of("op dv d b 0b1001010", 11),
                 error: statements::ParseError::InvalidInstruction(vec![
                     "op",
                     "dv",
@@ -265,13 +309,286 @@ fn errs() {
                 })
             }),
             Err(parser::ParseError::Statement {
-                line: 13,
+                span: span_of("jump jlbl1 always", 13),
                 error: parser::StatementParseErr::MissingJumpLabel("jlbl1",),
             })
         ]
     )
 }
 
+#[test]
+fn spans() {
+    const SRC: &str = "set a \"x; y\"; print a\n\n  jump 0 always";
+
+    let lexer: Lexer<Statement> = Lexer::new(SRC);
+    let spanned: Vec<_> = lexer.spanned().map(|x| x.unwrap()).collect();
+
+    assert_eq!(
+        spanned.iter().map(|x| x.span).collect::<Vec<_>>(),
+        [
+            Span {
+                start: 0,
+                end: 12,
+                line: 0,
+                column: 0
+            },
+            Span {
+                start: 14,
+                end: 21,
+                line: 0,
+                column: 14
+            },
+            Span {
+                start: 25,
+                end: 38,
+                line: 2,
+                column: 2
+            },
+        ]
+    );
+
+    let value = spanned[0].tokens[2];
+    assert_eq!(value.value, "\"x; y\"");
+    assert_eq!(&SRC[value.span.start..value.span.end], "\"x; y\"");
+    assert_eq!(value.span.column, 6);
+}
+
+#[test]
+fn arity() {
+    const SRC: &str = r#"
+        op add x
+        op add x 1 2 3 4
+        set a
+        set a 1 2
+        jump 0 always x false
+        draw clear 0 0 0 0 0 0
+    "#;
+
+    let lexer: Lexer<Statement> = Lexer::new(SRC);
+    assert_eq!(
+        lexer.map(|x| x.unwrap()).collect::<Vec<_>>(),
+        [
+            Statement::OpAdd {
+                c: "x",
+                a: Argument::Number(0.),
+                b: Argument::Number(0.)
+            },
+            Statement::OpAdd {
+                c: "x",
+                a: Argument::Number(1.),
+                b: Argument::Number(2.)
+            },
+            Statement::Set {
+                var: "a",
+                value: Argument::Number(0.)
+            },
+            Statement::Set {
+                var: "a",
+                value: Argument::Number(1.)
+            },
+            Statement::Jump {
+                target: JumpTarget::new(0),
+                cond: ConditionOp::Always,
+                lhs: None,
+                rhs: None
+            },
+            Statement::DrawClear {
+                r: Argument::Number(0.),
+                g: Argument::Number(0.),
+                b: Argument::Number(0.)
+            },
+        ]
+    );
+
+    let options = ParseOptions {
+        arity: Arity::Strict,
+        ..Default::default()
+    };
+    let lexer: Lexer<Statement> = Lexer::new(SRC).with_options(options);
+    assert_eq!(
+        lexer
+            .map(|x| x.map_err(|parser::ParseError::Statement { error, .. }| error))
+            .collect::<Vec<_>>(),
+        [
+            Err(statements::ParseError::TooFewOperands {
+                expected: 3,
+                actual: 1
+            }),
+            Err(statements::ParseError::TooManyOperands {
+                expected: 3,
+                actual: 5
+            }),
+            Err(statements::ParseError::TooFewOperands {
+                expected: 2,
+                actual: 1
+            }),
+            Err(statements::ParseError::TooManyOperands {
+                expected: 2,
+                actual: 3
+            }),
+            Ok(Statement::Jump {
+                target: JumpTarget::new(0),
+                cond: ConditionOp::Always,
+                lhs: None,
+                rhs: None
+            }),
+            Ok(Statement::DrawClear {
+                r: Argument::Number(0.),
+                g: Argument::Number(0.),
+                b: Argument::Number(0.)
+            }),
+        ]
+    );
+}
+
+#[test]
+fn parse_all() {
+    const SRC: &str = r#"
+        set a 1
+        op dv a a 2
+        print a
+        jump nowhere always
+        op add x
+    "#;
+
+    let options = ParseOptions {
+        arity: Arity::Strict,
+        ..Default::default()
+    };
+    let parsed = Lexer::<Statement>::new(SRC)
+        .with_options(options)
+        .parse_all();
+
+    assert!(!parsed.is_ok());
+    assert_eq!(
+        parsed
+            .statements
+            .iter()
+            .map(|x| (x.span.line, x.statement.clone()))
+            .collect::<Vec<_>>(),
+        [
+            (
+                1,
+                Statement::Set {
+                    var: "a",
+                    value: Argument::Number(1.)
+                }
+            ),
+            (
+                3,
+                Statement::Print {
+                    text: Argument::Variable("a")
+                }
+            ),
+        ]
+    );
+    assert_eq!(
+        parsed
+            .errors
+            .iter()
+            .map(|parser::ParseError::Statement { span, error }| (span.line, error))
+            .collect::<Vec<_>>(),
+        [
+            (
+                2,
+                &statements::ParseError::InvalidInstruction(vec!["op", "dv", "a", "a", "2"])
+            ),
+            (4, &statements::ParseError::MissingJumpLabel("nowhere")),
+            (
+                5,
+                &statements::ParseError::TooFewOperands {
+                    expected: 3,
+                    actual: 1
+                }
+            ),
+        ]
+    );
+}
+
+#[test]
+fn keep_unknown() {
+    const SRC: &str = r#"
+        set a 1
+        modded thing "with spaces" 2
+        setblock block a 1 2
+    "#;
+
+    let options = ParseOptions {
+        keep_unknown: true,
+        ..Default::default()
+    };
+
+    let parsed: Vec<_> = Lexer::<Statement>::new(SRC)
+        .with_options(options)
+        .map(|x| x.unwrap())
+        .collect();
+    assert_eq!(
+        parsed,
+        [
+            Statement::Set {
+                var: "a",
+                value: Argument::Number(1.)
+            },
+            Statement::Unknown {
+                tokens: vec!["modded", "thing", "\"with spaces\"", "2"]
+            },
+            Statement::Unknown {
+                tokens: vec!["setblock", "block", "a", "1", "2"]
+            },
+        ]
+    );
+    assert_eq!(
+        parsed.iter().map(|x| x.to_string()).collect::<Vec<_>>(),
+        [
+            "set a 1",
+            "modded thing \"with spaces\" 2",
+            "setblock block a 1 2"
+        ]
+    );
+
+    let parsed: Vec<_> = Lexer::<WprocStatement>::new(SRC)
+        .with_options(options)
+        .map(|x| x.unwrap())
+        .collect();
+    assert_eq!(
+        parsed[1],
+        WprocStatement::Unknown {
+            tokens: vec!["modded", "thing", "\"with spaces\"", "2"]
+        }
+    );
+    assert!(matches!(parsed[2], WprocStatement::SetBBlock { .. }));
+}
+
+#[test]
+fn jump_labels() {
+    const SRC: &str = r#"
+        start:
+            op add i i 1
+        middle:
+            jump start lessThan i 5
+        end:
+    "#;
+
+    let lexer: Lexer<Statement> = Lexer::new(SRC);
+    assert_eq!(
+        lexer.jump_labels(),
+        &HashMap::from([("start", 0), ("middle", 1), ("end", 2)])
+    );
+
+    let parsed = lexer.parse_all();
+    assert_eq!(parsed.jump_labels.len(), 3);
+    assert!(matches!(
+        parsed.statements[1].statement,
+        Statement::Jump {
+            target: JumpTarget {
+                index: 0,
+                label: Some("start")
+            },
+            ..
+        }
+    ));
+}
+
 #[test]
 fn display() {
     let tokens = [Statement::OpAdd {
@@ -283,3 +600,524 @@ fn display() {
     println!("{:#?}", tokens);
     assert_eq!(tokens.map(|x| x.to_string()), ["op add a -5 12"])
 }
+
+#[test]
+fn outputs_after_inputs() {
+    const SRC: &str = "radar enemy any any distance turret1 1 target\n\
+                       ulocate building core true @copper x y found core\n\
+                       ucontrol within x y 5 near 0";
+
+    let statements: Vec<Statement> = Lexer::new(SRC).map(|x| x.unwrap()).collect();
+
+    assert_eq!(
+        statements[0],
+        Statement::Radar {
+            m1: Argument::Variable("enemy"),
+            m2: Argument::Variable("any"),
+            m3: Argument::Variable("any"),
+            sort: Argument::Variable("distance"),
+            block: Argument::Variable("turret1"),
+            order: Argument::Number(1.),
+            result: "target",
+        }
+    );
+    assert!(matches!(
+        statements[1],
+        Statement::ULocate {
+            ore: Argument::GlobalVar("copper"),
+            outx: "x",
+            found: "found",
+            building: "core",
+            ..
+        }
+    ));
+    assert!(matches!(
+        statements[2],
+        Statement::UCWithin {
+            radius: Argument::Number(5.),
+            result: "near",
+            ..
+        }
+    ));
+    assert_eq!(
+        statements
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>(),
+        [
+            "radar enemy any any distance turret1 1 target",
+            "ulocate building core true @copper x y found core",
+            "ucontrol within x y 5 near",
+        ]
+    );
+}
+
+/// Parses a single statement, checking that it prints back to `src`
+fn round_trip<'a, S: StatementType<'a> + std::fmt::Debug>(src: &'a str) -> S {
+    let mut statements: Vec<S> = Lexer::new(src).map(|x| x.unwrap()).collect();
+    assert_eq!(statements.len(), 1);
+    let statement = statements.remove(0);
+    assert_eq!(statement.to_string(), src);
+    statement
+}
+
+#[test]
+fn sensor_operands() {
+    assert_eq!(
+        round_trip::<Statement>("sensor health turret1 @health"),
+        Statement::Sensor {
+            block: Argument::Variable("turret1"),
+            property: Argument::GlobalVar("health"),
+            result: "health",
+        }
+    );
+}
+
+#[test]
+fn radar_order() {
+    assert_eq!(
+        round_trip::<Statement>("radar enemy flying any health turret1 0 target"),
+        Statement::Radar {
+            m1: Argument::Variable("enemy"),
+            m2: Argument::Variable("flying"),
+            m3: Argument::Variable("any"),
+            sort: Argument::Variable("health"),
+            block: Argument::Variable("turret1"),
+            order: Argument::Number(0.),
+            result: "target",
+        }
+    );
+    assert_eq!(
+        round_trip::<Statement>("uradar enemy any any distance 0 1 target"),
+        Statement::URadar {
+            m1: Argument::Variable("enemy"),
+            m2: Argument::Variable("any"),
+            m3: Argument::Variable("any"),
+            sort: Argument::Variable("distance"),
+            block: Argument::Number(0.),
+            order: Argument::Number(1.),
+            result: "target",
+        }
+    );
+}
+
+#[test]
+fn control_block() {
+    assert_eq!(
+        round_trip::<Statement>("control enabled switch1 false"),
+        Statement::ControlEnabled {
+            block: Argument::Variable("switch1"),
+            enabled: Argument::Variable("false"),
+        }
+    );
+    assert_eq!(
+        round_trip::<Statement>("control config sorter1 @copper"),
+        Statement::ControlConfig {
+            block: Argument::Variable("sorter1"),
+            config: Argument::GlobalVar("copper"),
+        }
+    );
+    assert_eq!(
+        round_trip::<Statement>("control color illuminator1 colour"),
+        Statement::ControlColour {
+            block: Argument::Variable("illuminator1"),
+            colour: Argument::Variable("colour"),
+        }
+    );
+}
+
+#[test]
+fn ulocate_outputs() {
+    assert_eq!(
+        round_trip::<Statement>("ulocate ore core false @copper x y found building"),
+        Statement::ULocate {
+            find: Argument::Variable("ore"),
+            group: Argument::Variable("core"),
+            enemy: Argument::Variable("false"),
+            ore: Argument::GlobalVar("copper"),
+            outx: "x",
+            outy: "y",
+            found: "found",
+            building: "building",
+        }
+    );
+}
+
+#[test]
+fn ucontrol_within() {
+    assert_eq!(
+        round_trip::<Statement>("ucontrol within x y 5 near"),
+        Statement::UCWithin {
+            x: Argument::Variable("x"),
+            y: Argument::Variable("y"),
+            radius: Argument::Number(5.),
+            result: "near",
+        }
+    );
+}
+
+#[test]
+fn ucontrol_item_take() {
+    assert_eq!(
+        round_trip::<Statement>("ucontrol itemTake vault1 @silicon 30"),
+        Statement::UCItemTake {
+            from: Argument::Variable("vault1"),
+            item: Argument::GlobalVar("silicon"),
+            amount: Argument::Number(30.),
+        }
+    );
+}
+
+#[test]
+fn log_operands() {
+    const SRC: &str = "op log r x\nop logn r x 2";
+
+    let statements: Vec<Statement> = Lexer::new(SRC).map(|x| x.unwrap()).collect();
+
+    assert_eq!(
+        statements,
+        [
+            Statement::OpLog {
+                x: Argument::Variable("x"),
+                result: "r",
+            },
+            Statement::OpLogN {
+                a: Argument::Variable("x"),
+                b: Argument::Number(2.),
+                result: "r",
+            },
+        ]
+    );
+    assert_eq!(
+        statements
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>(),
+        SRC.lines().collect::<Vec<_>>()
+    );
+}
+
+#[test]
+fn poly_rotation() {
+    const SRC: &str = "draw poly 40 40 6 10 30\ndraw linePoly x y 3 r angle";
+
+    let statements: Vec<Statement> = Lexer::new(SRC).map(|x| x.unwrap()).collect();
+
+    assert_eq!(
+        statements,
+        [
+            Statement::DrawPoly {
+                x: Argument::Number(40.),
+                y: Argument::Number(40.),
+                w: Argument::Number(6.),
+                h: Argument::Number(10.),
+                rotation: Argument::Number(30.),
+            },
+            Statement::DrawLinePoly {
+                x: Argument::Variable("x"),
+                y: Argument::Variable("y"),
+                w: Argument::Number(3.),
+                h: Argument::Variable("r"),
+                rotation: Argument::Variable("angle"),
+            },
+        ]
+    );
+    assert_eq!(
+        statements
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>(),
+        SRC.lines().collect::<Vec<_>>()
+    );
+}
+
+#[test]
+fn getblock_layers() {
+    assert_eq!(
+        round_trip::<WprocStatement>("getblock floor result 1 2"),
+        WprocStatement::GetBFloor {
+            x: Argument::Number(1.),
+            y: Argument::Number(2.),
+            result: "result",
+        }
+    );
+    assert_eq!(
+        round_trip::<WprocStatement>("getblock ore result 1 2"),
+        WprocStatement::GetBOre {
+            x: Argument::Number(1.),
+            y: Argument::Number(2.),
+            result: "result",
+        }
+    );
+    assert_eq!(
+        round_trip::<WprocStatement>("getblock block result 1 2"),
+        WprocStatement::GetBBlock {
+            x: Argument::Number(1.),
+            y: Argument::Number(2.),
+            result: "result",
+        }
+    );
+    assert_eq!(
+        round_trip::<WprocStatement>("getblock building result 1 2"),
+        WprocStatement::GetBBuilding {
+            x: Argument::Number(1.),
+            y: Argument::Number(2.),
+            result: "result",
+        }
+    );
+}
+
+#[test]
+fn setblock_operands() {
+    assert_eq!(
+        round_trip::<WprocStatement>("setblock floor @stone 1 2"),
+        WprocStatement::SetBFloor {
+            to: Argument::GlobalVar("stone"),
+            x: Argument::Number(1.),
+            y: Argument::Number(2.),
+        }
+    );
+    assert_eq!(
+        round_trip::<WprocStatement>("setblock ore @ore-copper 1 2"),
+        WprocStatement::SetBOre {
+            to: Argument::GlobalVar("ore-copper"),
+            x: Argument::Number(1.),
+            y: Argument::Number(2.),
+        }
+    );
+    assert_eq!(
+        round_trip::<WprocStatement>("setblock block @router 1 2 @sharded 3"),
+        WprocStatement::SetBBlock {
+            to: Argument::GlobalVar("router"),
+            x: Argument::Number(1.),
+            y: Argument::Number(2.),
+            team: Argument::GlobalVar("sharded"),
+            rotation: Argument::Number(3.),
+        }
+    );
+}
+
+#[test]
+fn fetch_counts() {
+    assert_eq!(
+        round_trip::<WprocStatement>("fetch unitCount count @crux 0 @dagger"),
+        WprocStatement::FetchUnitCount {
+            team: Argument::GlobalVar("crux"),
+            _1: Argument::Number(0.),
+            unit: Argument::GlobalVar("dagger"),
+            result: "count",
+        }
+    );
+    assert_eq!(
+        round_trip::<WprocStatement>("fetch buildCount count @crux 0 @router"),
+        WprocStatement::FetchBuildCount {
+            team: Argument::GlobalVar("crux"),
+            _1: Argument::Number(0.),
+            block: Argument::GlobalVar("router"),
+            result: "count",
+        }
+    );
+}
+
+#[test]
+fn playsound() {
+    assert_eq!(
+        round_trip::<WprocStatement>("playsound true @sfx-pew 1 2 0 x y true"),
+        WprocStatement::PlaySoundPositional {
+            sound: Argument::GlobalVar("sfx-pew"),
+            volume: Argument::Number(1.),
+            pitch: Argument::Number(2.),
+            _1: Argument::Number(0.),
+            x: Argument::Variable("x"),
+            y: Argument::Variable("y"),
+            limit: Argument::Variable("true"),
+        }
+    );
+    assert_eq!(
+        round_trip::<WprocStatement>("playsound false @sfx-pew 1 2 -1 0 0 true"),
+        WprocStatement::PlaySoundGlobal {
+            sound: Argument::GlobalVar("sfx-pew"),
+            volume: Argument::Number(1.),
+            pitch: Argument::Number(2.),
+            pan: Argument::Number(-1.),
+            _1: Argument::Number(0.),
+            _2: Argument::Number(0.),
+            limit: Argument::Variable("true"),
+        }
+    );
+}
+
+#[test]
+fn spawnwave_operands() {
+    assert_eq!(
+        round_trip::<WprocStatement>("spawnwave 10 20 false"),
+        WprocStatement::SpawnWave {
+            x: Argument::Number(10.),
+            y: Argument::Number(20.),
+            natural: Argument::Variable("false"),
+        }
+    );
+}
+
+#[test]
+fn team_setrules() {
+    assert_eq!(
+        round_trip::<WprocStatement>("setrule unitHealth 2 @crux"),
+        WprocStatement::SetRUnitHealth {
+            v: Argument::Number(2.),
+            team: Argument::GlobalVar("crux"),
+        }
+    );
+
+    for rule in [
+        "buildSpeed",
+        "unitHealth",
+        "unitBuildSpeed",
+        "unitMineSpeed",
+        "unitCost",
+        "unitDamage",
+        "blockHealth",
+        "blockDamage",
+        "rtsMinWeight",
+        "rtsMinSquad",
+    ] {
+        let src = format!("setrule {rule} 2 @crux");
+        let statement = round_trip::<WprocStatement>(&src);
+        assert!(!matches!(statement, WprocStatement::Unknown { .. }));
+    }
+}
+
+#[test]
+fn map_area() {
+    assert_eq!(
+        round_trip::<WprocStatement>("setrule mapArea 0 1 2 30 40"),
+        WprocStatement::SetRMapArea {
+            _1: Argument::Number(0.),
+            x: Argument::Number(1.),
+            y: Argument::Number(2.),
+            w: Argument::Number(30.),
+            h: Argument::Number(40.),
+        }
+    );
+}
+
+#[test]
+fn effect_operands() {
+    let (x, y) = (Argument::Variable("x"), Argument::Variable("y"));
+    let colour = Argument::Variable("colour");
+    assert_eq!(
+        round_trip::<WprocStatement>("effect trail x y 3 colour"),
+        WprocStatement::EffectTrail {
+            x,
+            y,
+            size: Argument::Number(3.),
+            colour,
+        }
+    );
+    assert_eq!(
+        round_trip::<WprocStatement>("effect hit x y 0 colour"),
+        WprocStatement::EffectHit {
+            x,
+            y,
+            _1: Argument::Number(0.),
+            colour,
+        }
+    );
+    assert_eq!(
+        round_trip::<WprocStatement>("effect shootBig x y 45 colour"),
+        WprocStatement::EffectShootBig {
+            x,
+            y,
+            rotation: Argument::Number(45.),
+            colour,
+        }
+    );
+    assert_eq!(
+        round_trip::<WprocStatement>("effect spark x y 0 colour"),
+        WprocStatement::EffectSpark {
+            x,
+            y,
+            _1: Argument::Number(0.),
+            colour,
+        }
+    );
+    assert_eq!(
+        round_trip::<WprocStatement>("effect blockFall x y 0 0 @router"),
+        WprocStatement::EffectBlockFall {
+            x,
+            y,
+            _1: Argument::Number(0.),
+            _2: Argument::Number(0.),
+            data: Argument::GlobalVar("router"),
+        }
+    );
+
+    for effect in [
+        "trail",
+        "breakProp",
+        "wave",
+        "lightBlock",
+        "crossExplosion",
+        "smokeCloud",
+        "vapor",
+        "hit",
+        "hitSquare",
+        "smokePuff",
+        "drill",
+        "drillBig",
+        "sparkExplosion",
+        "shootBig",
+        "shootSmall",
+        "smokeColor",
+        "smokeSquare",
+        "smokeSquareBig",
+        "sparkShoot",
+        "sparkShootBig",
+        "smokeSmall",
+        "smokeBig",
+        "spark",
+        "sparkBig",
+    ] {
+        let src = format!("effect {effect} x y 2 colour");
+        let statement = round_trip::<WprocStatement>(&src);
+        assert!(!matches!(statement, WprocStatement::Unknown { .. }));
+    }
+}
+
+#[test]
+fn wproc_conversions() {
+    let statement = Statement::Sensor {
+        block: Argument::Variable("turret1"),
+        property: Argument::GlobalVar("health"),
+        result: "health",
+    };
+    let wproc = WprocStatement::from(statement.clone());
+    assert_eq!(
+        wproc,
+        WprocStatement::Sensor {
+            block: Argument::Variable("turret1"),
+            property: Argument::GlobalVar("health"),
+            result: "health",
+        }
+    );
+    assert_eq!(Statement::try_from(wproc), Ok(statement));
+
+    let jump = Statement::Jump {
+        target: JumpTarget::new(3),
+        cond: ConditionOp::Always,
+        lhs: None,
+        rhs: None,
+    };
+    assert_eq!(
+        Statement::try_from(WprocStatement::from(jump.clone())),
+        Ok(jump)
+    );
+
+    // Statements only world processors can run are given back
+    let spawn_wave = WprocStatement::SpawnWave {
+        x: Argument::Number(10.),
+        y: Argument::Number(20.),
+        natural: Argument::Variable("false"),
+    };
+    assert_eq!(Statement::try_from(spawn_wave.clone()), Err(spawn_wave));
+}
@@ -0,0 +1,80 @@
+use crate::parser::{
+    lexer::Lexer,
+    statements::{Statement, StatementType},
+};
+use crate::printer::LabelPrinter;
+use pretty_assertions::assert_eq;
+
+/// Removes the label names, since they aren't kept when reparsing generated labels
+fn without_labels<'a>(mut statements: Vec<Statement<'a>>) -> Vec<Statement<'a>> {
+    for statement in &mut statements {
+        if let Some(target) = statement.jump_target_mut() {
+            target.label = None;
+        }
+    }
+    statements
+}
+
+#[test]
+fn generated_labels() {
+    const SRC: &str = r#"
+        set i 0
+        op add i i 1
+        jump 1 lessThan i 5
+        jump 4 always
+        end
+        jump 0 always
+    "#;
+
+    let statements: Vec<Statement> = Lexer::new(SRC).map(|x| x.unwrap()).collect();
+
+    assert_eq!(
+        LabelPrinter::new(&statements).to_string(),
+        "label0:\nset i 0\nlabel1:\nop add i i 1\njump label1 lessThan i 5\njump label2 always\n\
+         label2:\nend\njump label0 always\n"
+    );
+}
+
+#[test]
+fn known_labels() {
+    const SRC: &str = r#"
+        label0:
+            set i 0
+        unused:
+        label1:
+            op add i i 1
+        jump label1 lessThan i 5
+        jump 0 always
+        jump 5 always
+    "#;
+
+    let lexer: Lexer<Statement> = Lexer::new(SRC);
+    let labels = lexer.jump_labels().clone();
+    let statements: Vec<_> = lexer.map(|x| x.unwrap()).collect();
+
+    // Generated names shouldn't clash with existing ones
+    assert_eq!(
+        LabelPrinter::new(&statements).to_string(),
+        "label0:\nset i 0\nlabel1:\nop add i i 1\njump label1 lessThan i 5\njump label0 always\n\
+         jump label2 always\nlabel2:\n"
+    );
+    assert_eq!(
+        LabelPrinter::new(&statements)
+            .with_labels(&labels)
+            .to_string(),
+        "label0:\nset i 0\nlabel1:\nunused:\nop add i i 1\njump label1 lessThan i 5\n\
+         jump label0 always\njump label2 always\nlabel2:\n"
+    );
+}
+
+#[test]
+fn round_trip() {
+    const SRC: &str = include_str!("../../mlog_files/golem/mandelbrot.mlog");
+
+    let statements: Vec<Statement> = Lexer::new(SRC).map(|x| x.unwrap()).collect();
+    let printed = LabelPrinter::new(&statements).to_string();
+    let reparsed: Vec<Statement> = Lexer::new(&printed).map(|x| x.unwrap()).collect();
+
+    assert!(!printed.contains("jump 0 "));
+    assert_eq!(without_labels(reparsed), without_labels(statements));
+}
@@ -0,0 +1,119 @@
+use crate::parser::{
+    args::{Argument, ConditionOp},
+    statements::{JumpTarget, Statement},
+};
+use crate::program::Program;
+use pretty_assertions::assert_eq;
+
+const SRC: &str = r#"
+    set i 0
+    loop:
+        op add i i 1
+        print i
+    jump loop lessThan i 5
+    jump 5 always
+    end
+"#;
+
+fn targets(program: &Program<Statement>) -> Vec<usize> {
+    program
+        .statements()
+        .iter()
+        .filter_map(|x| match x {
+            Statement::Jump { target, .. } => Some(target.index),
+            _ => None,
+        })
+        .collect()
+}
+
+#[test]
+fn insert() {
+    let mut program: Program<Statement> = Program::parse(SRC).unwrap();
+
+    program.insert(1, Statement::Noop {});
+    assert_eq!(targets(&program), [2, 6]);
+    assert_eq!(program.labels()["loop"], 2);
+
+    program.insert(3, Statement::Noop {});
+    assert_eq!(targets(&program), [2, 7]);
+    assert_eq!(program.len(), 8);
+}
+
+#[test]
+fn remove() {
+    let mut program: Program<Statement> = Program::parse(SRC).unwrap();
+
+    assert_eq!(
+        program.remove(0),
+        Statement::Set {
+            var: "i",
+            value: Argument::Number(0.)
+        }
+    );
+    assert_eq!(targets(&program), [0, 4]);
+
+    // Jumps to the removed statement now go to the one after it
+    program.remove(0);
+    assert_eq!(targets(&program), [0, 3]);
+    assert_eq!(program.labels()["loop"], 0);
+}
+
+#[test]
+fn splice() {
+    let mut program: Program<Statement> = Program::parse(SRC).unwrap();
+
+    let removed = program.splice(
+        1..3,
+        [Statement::Noop {}, Statement::Noop {}, Statement::Noop {}],
+    );
+    assert_eq!(removed.len(), 2);
+    assert_eq!(targets(&program), [1, 6]);
+    assert_eq!(program.labels()["loop"], 1);
+}
+
+#[test]
+fn append() {
+    let mut program: Program<Statement> = Program::parse(SRC).unwrap();
+    let other: Program<Statement> =
+        Program::parse("again:\nop sub i i 1\njump again greaterThan i 0").unwrap();
+
+    program.append(other);
+    assert_eq!(targets(&program), [1, 5, 6]);
+    assert_eq!(program.labels()["again"], 6);
+    assert_eq!(
+        program.statements()[7],
+        Statement::Jump {
+            target: JumpTarget::labelled(6, "again"),
+            cond: ConditionOp::GreaterThan,
+            lhs: Some(Argument::Variable("i")),
+            rhs: Some(Argument::Number(0.))
+        }
+    );
+}
+
+#[test]
+fn append_clashing_labels() {
+    const OTHER: &str = "loop:\nop sub i i 1\njump loop greaterThan i 0";
+
+    let mut program: Program<Statement> = Program::parse(SRC).unwrap();
+    program.append(Program::parse(OTHER).unwrap());
+    assert_eq!(targets(&program), [1, 5, 6]);
+    assert_eq!(program.labels()["loop"], 1);
+
+    let printed = program.to_string();
+    assert_eq!(printed.matches("loop:").count(), 1);
+    let reparsed: Program<Statement> = Program::parse(&printed).unwrap();
+    assert_eq!(targets(&reparsed), targets(&program));
+}
+
+#[test]
+fn display() {
+    let mut program: Program<Statement> = Program::parse(SRC).unwrap();
+    program.push(Statement::Stop {});
+
+    assert_eq!(
+        program.to_string(),
+        "set i 0\nloop:\nop add i i 1\nprint i\njump loop lessThan i 5\njump label0 always\n\
+         label0:\nend\nstop\n"
+    );
+}
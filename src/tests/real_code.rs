@@ -1,6 +1,6 @@
 use crate::parser::{
     lexer::Lexer,
-    statements::{Statement, WprocStatement},
+    statements::{Arity, ParseOptions, Statement, WprocStatement},
 };
 
 // These are all tests to see if they parse *at all*, not for parsing correctness
@@ -52,3 +52,23 @@ fn unit_transport() {
     let lexer: Lexer<Statement> = Lexer::new(SRC);
     let _ = lexer.map(|x| x.unwrap()).collect::<Vec<_>>();
 }
+
+#[test]
+fn strict_arity() {
+    const SRCS: [&str; 5] = [
+        include_str!("../../mlog_files/golem/mandelbrot.mlog"),
+        include_str!("../../mlog_files/golem/odd_supply.mlog"),
+        include_str!("../../mlog_files/golem/base_builder.mlog"),
+        include_str!("../../mlog_files/golem/power_plant.mlog"),
+        include_str!("../../mlog_files/golem/unit_transport.mlog"),
+    ];
+    let options = ParseOptions {
+        arity: Arity::Strict,
+        ..Default::default()
+    };
+
+    for src in SRCS {
+        let lexer: Lexer<Statement> = Lexer::new(src).with_options(options);
+        let _ = lexer.map(|x| x.unwrap()).collect::<Vec<_>>();
+    }
+}
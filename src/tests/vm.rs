@@ -0,0 +1,1488 @@
+use crate::parser::{args::Rgba, statements::Statement};
+use crate::program::Program;
+use crate::vm::{
+    Block, Processor, Simulation, Step, Value,
+    blocks::Memory,
+    clock::{MAX_IPT, ProcessorKind},
+    debug::{Debugger, Pause},
+    display::{Display, DrawCommand},
+    profile::Profile,
+    trace::{Effect, Trace},
+    world::{
+        ControlCommand, LocateQuery, Located, RadarQuery, Recorder, UnitCommand, World, WorldEvent,
+        WorldQuery,
+    },
+};
+use pretty_assertions::assert_eq;
+use std::collections::HashMap;
+
+#[test]
+fn loops() {
+    const SRC: &str = r#"
+        set i 0
+        set total 0
+        loop:
+            op add total total i
+            op add i i 1
+        jump loop lessThan i 10
+        end
+        set unreachable true
+    "#;
+    let mut processor = Processor::parse(SRC).unwrap();
+
+    assert_eq!(processor.run(1000), Step::End);
+    assert_eq!(processor.get("total"), Value::Number(45.));
+    assert_eq!(processor.get("unreachable"), Value::Null);
+
+    // It starts again from the top
+    processor.step();
+    assert_eq!(processor.counter(), 1);
+}
+
+#[test]
+fn counter() {
+    const SRC: &str = r#"
+        set x @counter
+        op add @counter @counter 1
+        set skipped true
+        set y 5
+        set @counter -1
+    "#;
+    let mut processor = Processor::parse(SRC).unwrap();
+
+    assert_eq!(processor.run(3), Step::Continue);
+    assert_eq!(processor.get("x"), Value::Number(1.));
+    assert_eq!(processor.get("skipped"), Value::Null);
+    assert_eq!(processor.get("y"), Value::Number(5.));
+
+    // Negative counters wrap around to the start
+    processor.step();
+    assert_eq!(processor.counter(), 0);
+}
+
+#[test]
+fn stop() {
+    let mut processor = Processor::parse("set a 1\nstop\nset b 2").unwrap();
+
+    assert_eq!(processor.run(100), Step::Stopped);
+    assert_eq!(processor.run(100), Step::Stopped);
+    assert_eq!(processor.counter(), 1);
+    assert_eq!(processor.get("b"), Value::Null);
+}
+
+#[test]
+fn constants() {
+    const SRC: &str = r#"
+        set true 5
+        set @pi 5
+        set 5 6
+        set a true
+        set b null
+        set c "a\nb"
+        set d %00000001
+        set e @pi
+    "#;
+    let mut processor = Processor::parse(SRC).unwrap();
+    processor.run(100);
+
+    assert_eq!(processor.get("true"), Value::Number(1.));
+    assert_eq!(
+        processor.get("@pi"),
+        Value::Number(std::f32::consts::PI.into())
+    );
+    assert_eq!(processor.get("a"), Value::Number(1.));
+    assert_eq!(processor.get("b"), Value::Null);
+    assert_eq!(processor.get("c"), Value::from("a\nb"));
+    assert_eq!(processor.get("d"), Value::Number(f64::from_bits(1)));
+    assert_eq!(
+        processor.get("e"),
+        Value::Number(std::f32::consts::PI.into())
+    );
+
+    let mut names: Vec<_> = processor.variables().map(|(k, _)| k).collect();
+    names.sort_unstable();
+    assert_eq!(names, ["a", "b", "c", "d", "e"]);
+}
+
+#[test]
+fn ops_and_select() {
+    const SRC: &str = r#"
+        op div a 1 0
+        op idiv b -7 2
+        op sin c 90
+        op equal d "x" "y"
+        op equal e "x" 1
+        op strictEqual f 1 "x"
+        select g lessThan 1 2 "yes" "no"
+        select h strictEqual null 0 "yes" "no"
+        select i equal null 0 "yes" "no"
+    "#;
+    let mut processor = Processor::parse(SRC).unwrap();
+    processor.run(100);
+
+    assert_eq!(processor.get("a"), Value::Null);
+    assert_eq!(processor.get("b"), Value::Number(-4.));
+    assert_eq!(processor.get("c"), Value::Number(1.));
+    assert_eq!(processor.get("d"), Value::Number(0.));
+    assert_eq!(processor.get("e"), Value::Number(1.));
+    assert_eq!(processor.get("f"), Value::Number(0.));
+    assert_eq!(processor.get("g"), Value::from("yes"));
+    assert_eq!(processor.get("h"), Value::from("no"));
+    assert_eq!(processor.get("i"), Value::from("yes"));
+}
+
+#[test]
+fn rand() {
+    let run = |seed| {
+        let mut processor = Processor::parse("op rand x 10").unwrap().with_seed(seed);
+        processor.step();
+        processor.get("x").num()
+    };
+
+    assert_eq!(run(1), run(1));
+    assert!((0. ..10.).contains(&run(2)));
+}
+
+#[test]
+fn empty() {
+    let mut processor = Processor::new(Vec::new());
+    assert_eq!(processor.run(10), Step::End);
+}
+
+#[test]
+fn logic_ops() {
+    use crate::vm::ops::LogicOp as Op;
+
+    let eval = |op: Op, a: f64, b: f64| op.eval(&a.into(), &b.into(), 0.5);
+    let num = |x: f64| Value::Number(x);
+
+    assert_eq!(eval(Op::IntDiv, -7., 2.), num(-4.));
+    assert_eq!(eval(Op::Mod, -7., 3.), num(-1.));
+    assert_eq!(eval(Op::TrueMod, -7., 3.), num(2.));
+    assert_eq!(eval(Op::Pow, 2., 0.5), num(2f64.sqrt()));
+    assert_eq!(eval(Op::Div, 1., 0.), Value::Null);
+    assert_eq!(eval(Op::Pow, 10., 400.), Value::Null);
+    assert_eq!(eval(Op::Sqrt, -1., 0.), Value::Null);
+    assert_eq!(eval(Op::LogN, 8., 2.), num(3.));
+    assert_eq!(eval(Op::Log, 1., 0.), num(0.));
+
+    assert_eq!(eval(Op::Equal, 1., 1.0000001), num(1.));
+    assert_eq!(eval(Op::StrictEqual, 1., 1.0000001), num(0.));
+    assert_eq!(eval(Op::LAnd, 2., 0.), num(0.));
+
+    // Bitwise operations go through 64-bit integers, and shifts only use the lowest 6 bits
+    assert_eq!(eval(Op::Shl, 1., 65.), num(2.));
+    assert_eq!(eval(Op::Shr, -8., 1.), num(-4.));
+    assert_eq!(eval(Op::UShr, -1., 60.), num(15.));
+    assert_eq!(eval(Op::Or, 5.7, 2.2), num(7.));
+    assert_eq!(eval(Op::Xor, 6., 3.), num(5.));
+    assert_eq!(eval(Op::Flip, 0., 0.), num(-1.));
+    assert_eq!(eval(Op::BAnd, 1e300, -1.), num(i64::MAX as f64));
+
+    assert_eq!(eval(Op::Round, 2.5, 0.), num(3.));
+    assert_eq!(eval(Op::Round, -2.5, 0.), num(-2.));
+    assert_eq!(eval(Op::Sign, -3., 0.), num(-1.));
+    assert_eq!(eval(Op::Sign, 0., 0.), num(0.));
+    assert_eq!(eval(Op::Min, 1., -2.), num(-2.));
+    assert_eq!(eval(Op::Rand, 10., 0.), num(5.));
+
+    // Angles are in degrees, and some operations are done with floats
+    assert_eq!(eval(Op::Sin, 30., 0.), num(30f64.to_radians().sin()));
+    assert_eq!(eval(Op::ATan, 1., 0.), num(45.));
+    assert_eq!(eval(Op::Angle, 0., 1.), num(90.));
+    assert_eq!(eval(Op::Angle, -1., 0.), num(180.));
+    assert_eq!(eval(Op::AngleDiff, 350., 10.), num(20.));
+    assert_eq!(eval(Op::AngleDiff, -90., 90.), num(180.));
+    assert_eq!(eval(Op::Len, 3., 4.), num(5.));
+    assert_eq!(eval(Op::Len, 0.1, 0.), num(0.1f32.into()));
+    assert_eq!(eval(Op::Noise, 0., 0.), num(0.));
+    assert!((-1. ..=1.).contains(&eval(Op::Noise, 12.3, 45.6).num()));
+
+    // Objects are compared directly, and count as 1 (or 0 for null) everywhere else
+    let s = Value::from("a");
+    assert_eq!(Op::Equal.eval(&s, &"a".into(), 0.), num(1.));
+    assert_eq!(Op::Equal.eval(&s, &"b".into(), 0.), num(0.));
+    assert_eq!(Op::Equal.eval(&s, &1.0.into(), 0.), num(1.));
+    assert_eq!(Op::StrictEqual.eval(&s, &1.0.into(), 0.), num(0.));
+    assert_eq!(Op::Equal.eval(&Value::Null, &0.0.into(), 0.), num(1.));
+    assert_eq!(Op::Add.eval(&s, &Value::Null, 0.), num(1.));
+}
+
+#[test]
+fn conditions() {
+    use crate::parser::args::ConditionOp as C;
+    use crate::vm::ops::condition;
+
+    let ops = [
+        C::Equal,
+        C::NotEqual,
+        C::StrictEqual,
+        C::StrictNotEqual,
+        C::LessThan,
+        C::LessThanEq,
+        C::GreaterThan,
+        C::GreaterThanEq,
+        C::Always,
+    ];
+    let values = [
+        Value::Null,
+        Value::Number(0.),
+        Value::Number(1.),
+        Value::Number(1.0000001),
+        Value::Number(-2.),
+        Value::from("a"),
+        Value::from("b"),
+        Value::Content("copper".into()),
+    ];
+
+    for op in ops {
+        for a in &values {
+            for b in &values {
+                if let Some(inverted) = op.inverted() {
+                    assert_ne!(
+                        condition(op, a, b),
+                        condition(inverted, a, b),
+                        "{op} {a} {b}"
+                    );
+                }
+                assert_eq!(
+                    condition(op, a, b),
+                    condition(op.swapped(), b, a),
+                    "{op} {a} {b}"
+                );
+            }
+        }
+    }
+
+    assert!(condition(
+        C::Equal,
+        &Value::Number(1.),
+        &Value::Number(1.0000001)
+    ));
+    assert!(!condition(
+        C::StrictEqual,
+        &Value::Number(1.),
+        &Value::Number(1.0000001)
+    ));
+    assert!(condition(C::Equal, &Value::from("a"), &Value::Number(1.)));
+    assert!(!condition(C::Equal, &Value::from("a"), &Value::from("b")));
+}
+
+#[test]
+fn values() {
+    const SRC: &str = r#"
+        set item @copper
+        set same @copper
+        op equal a item same
+        op equal b item @lead
+        op strictEqual c item 1
+        op equal d item 1
+        op add e item 1
+        set f @unit
+        jump 10 strictEqual f null
+        set g "unreachable"
+    "#;
+    let mut processor = Processor::parse(SRC).unwrap();
+    processor.run(100);
+
+    assert_eq!(processor.get("item"), Value::Content("copper".into()));
+    assert_eq!(processor.get("a"), Value::Number(1.));
+    assert_eq!(processor.get("b"), Value::Number(0.));
+    assert_eq!(processor.get("c"), Value::Number(0.));
+    assert_eq!(processor.get("d"), Value::Number(1.));
+    assert_eq!(processor.get("e"), Value::Number(2.));
+    assert_eq!(processor.get("g"), Value::Null);
+
+    assert!(Value::Building("cell1".into()).is_truthy());
+    assert!(Value::Unit(0).is_truthy());
+    assert!(Value::from("").is_truthy());
+    assert!(!Value::Number(0.000001).is_truthy());
+    assert!(!Value::Building("cell1".into()).loose_eq(&Value::Building("cell2".into())));
+    assert!(!Value::Null.loose_eq(&Value::from("a")));
+    assert!(Value::Null.loose_eq(&Value::Number(0.)));
+}
+
+#[test]
+fn memory() {
+    const SRC: &str = r#"
+        read a cell1 0
+        read b cell1 63
+        read e bank1 1.9
+        write 7 cell1 -0.5
+        set mem bank1
+        write 3 mem 511
+        read x mem 511
+    "#;
+    let mut processor = Processor::parse(SRC).unwrap();
+    processor.link("cell1", Memory::cell());
+    processor.link("bank1", Memory::bank());
+
+    let cell = processor.memory_mut("cell1").unwrap();
+    cell.values_mut()[0] = 3.;
+    cell.values_mut()[63] = 4.;
+    processor.memory_mut("bank1").unwrap().write(1., 2.);
+
+    processor.run(100);
+
+    assert_eq!(processor.get("a"), Value::Number(3.));
+    assert_eq!(processor.get("b"), Value::Number(4.));
+    // Indexes round down
+    assert_eq!(processor.get("e"), Value::Number(2.));
+    assert_eq!(processor.memory("cell1").unwrap().values()[0], 7.);
+    assert_eq!(processor.get("mem"), Value::Building("bank1".into()));
+    assert_eq!(processor.get("x"), Value::Number(3.));
+    assert_eq!(processor.memory("bank1").unwrap().values()[511], 3.);
+}
+
+#[test]
+fn memory_out_of_bounds() {
+    const SRC: &str = r#"
+        read c cell1 64
+        set d 5
+        read d cell1 -1
+        write 3 bank1 512
+        write 3 cell1 64
+        set f 1
+        read f nothing 0
+        write 1 nothing 0
+    "#;
+    let mut processor = Processor::parse(SRC).unwrap();
+    processor.link("cell1", Memory::cell());
+    processor.link("bank1", Memory::bank());
+    processor.memory_mut("cell1").unwrap().values_mut().fill(1.);
+
+    processor.run(100);
+
+    assert_eq!(processor.get("c"), Value::Number(0.));
+    assert_eq!(processor.get("d"), Value::Number(0.));
+    assert_eq!(processor.memory("bank1").unwrap().len(), 512);
+    assert_eq!(processor.memory("cell1").unwrap().len(), 64);
+    // Reading from something that isn't memory leaves the result alone
+    assert_eq!(processor.get("f"), Value::Number(1.));
+}
+
+#[test]
+fn links_are_constant() {
+    let mut processor = Processor::parse("set cell1 5").unwrap();
+    processor.link("cell1", Memory::cell());
+    processor.run(100);
+    assert_eq!(processor.get("cell1"), Value::Building("cell1".into()));
+
+    processor.set("cell1", Value::Null);
+    assert_eq!(processor.get("cell1"), Value::Building("cell1".into()));
+}
+
+#[test]
+fn format() {
+    const SRC: &str = r#"
+        print "{1}: {0} {0}"
+        format "a"
+        format 3
+        format 4
+        printflush message1
+        print "{0} {1}"
+        format 3.0000001
+        format @copper
+    "#;
+    let mut processor = Processor::parse(SRC).unwrap();
+    processor.link("message1", Block::message());
+    processor.run(100);
+
+    // The lowest numbered placeholder is filled first, wherever it is, one at a time
+    assert_eq!(processor.message("message1"), Some("4: a 3"));
+    assert_eq!(processor.text_buffer(), "3 copper");
+}
+
+#[test]
+fn print_values() {
+    const SRC: &str = r#"
+        print 1
+        print " "
+        print 0.1
+        print " "
+        print -2.5
+        print " "
+        print 12345678.9
+        print " "
+        print 0.00001
+        print " "
+        print 1e20
+        print " "
+        print 0.001
+        print " "
+        print null
+        print " "
+        printchar 65
+        printchar 0x1F600
+        printchar @copper
+    "#;
+    let mut processor = Processor::parse(SRC).unwrap();
+    processor.run(100);
+
+    // Only the lowest 16 bits of characters are kept, and content isn't printed
+    assert_eq!(
+        processor.text_buffer(),
+        "1 0.1 -2.5 1.23456789E7 1.0E-5 1.0E20 0.001 null A\u{f600}"
+    );
+}
+
+#[test]
+fn printflush() {
+    const SRC: &str = r#"
+        print "a\nb"
+        printflush message1
+        print "c"
+        printflush nothing
+        print "d"
+    "#;
+    let mut processor = Processor::parse(SRC).unwrap();
+    processor.link("message1", Block::message());
+    processor.run(100);
+
+    assert_eq!(processor.message("message1"), Some("a\nb"));
+    // The buffer is cleared even if there's nothing to flush it to
+    assert_eq!(processor.text_buffer(), "d");
+}
+
+#[test]
+fn print_limit() {
+    const SRC: &str = r#"
+        print "0123456789"
+        jump 0 always
+        printflush message1
+        printchar 65
+    "#;
+    let mut processor = Processor::parse(SRC).unwrap();
+    processor.link("message1", Block::message());
+
+    processor.run(80);
+    assert_eq!(processor.text_buffer().len(), 400);
+    processor.run(2);
+    assert_eq!(processor.text_buffer().len(), 400);
+
+    // `printchar` stops at the limit too
+    processor.set_counter(3);
+    processor.step();
+    assert_eq!(processor.text_buffer().len(), 400);
+
+    processor.set_counter(2);
+    processor.step();
+    assert_eq!(processor.message("message1").map(str::len), Some(400));
+    assert_eq!(processor.text_buffer(), "");
+}
+
+fn lit_pixels(display: &Display) -> Vec<(usize, usize)> {
+    let size = display.size();
+    (0..size)
+        .flat_map(|y| (0..size).map(move |x| (x, y)))
+        .filter(|(x, y)| display.pixel(*x, *y).unwrap().r > 0)
+        .collect()
+}
+
+#[test]
+fn draw_shapes() {
+    let draw = |src: &str| {
+        let mut processor = Processor::parse(src).unwrap();
+        processor.link("display1", Display::logic());
+        processor.run(100);
+        lit_pixels(processor.display("display1").unwrap())
+    };
+
+    assert_eq!(
+        draw("draw rect 1 2 2 1\ndrawflush display1\nstop"),
+        [(1, 2), (2, 2)]
+    );
+    // Negative sizes go the other way
+    assert_eq!(
+        draw("draw rect 3 3 -2 -1\ndrawflush display1\nstop"),
+        [(1, 2), (2, 2)]
+    );
+    assert_eq!(
+        draw("draw line 1 1 3 1\ndrawflush display1\nstop"),
+        [(1, 1), (2, 1), (3, 1)]
+    );
+    assert_eq!(
+        draw("draw stroke 1\ndraw lineRect 0 0 3 3\ndrawflush display1\nstop"),
+        [
+            (0, 0),
+            (1, 0),
+            (2, 0),
+            (0, 1),
+            (2, 1),
+            (0, 2),
+            (1, 2),
+            (2, 2)
+        ]
+    );
+    assert_eq!(
+        draw("draw triangle 0 0 4 0 0 4\ndrawflush display1\nstop").len(),
+        10
+    );
+    assert_eq!(
+        draw("draw poly 40 40 4 10\ndrawflush display1\nstop").len(),
+        206
+    );
+
+    // Nothing is drawn without a flush
+    assert_eq!(draw("draw rect 0 0 10 10\nstop"), []);
+}
+
+#[test]
+fn draw_transforms() {
+    let draw = |src: &str| {
+        let mut processor = Processor::parse(src).unwrap();
+        processor.link("display1", Display::logic());
+        processor.run(100);
+        lit_pixels(processor.display("display1").unwrap())
+    };
+
+    assert_eq!(
+        draw("draw translate 10 20\ndraw scale 2 2\ndraw rect 0 0 1 1\ndrawflush display1\nstop"),
+        [(10, 20), (11, 20), (10, 21), (11, 21)]
+    );
+    assert_eq!(
+        draw("draw translate 5 5\ndraw rotate 90\ndraw rect 0 0 2 1\ndrawflush display1\nstop"),
+        [(4, 5), (4, 6)]
+    );
+    assert_eq!(
+        draw(
+            "draw rotate 90\ndraw rect 0 0 2 1\ndraw reset\ndraw rect 5 5 1 1\ndrawflush display1\nstop"
+        ),
+        [(5, 5)]
+    );
+}
+
+#[test]
+fn display_output() {
+    const SRC: &str = r#"
+        draw clear 0 0 255
+        draw color 255 0 0 128
+        draw rect 0 0 1 1
+        draw col %00ff00
+        draw rect 1 1 1 1
+        drawflush display1
+        stop
+    "#;
+    let mut processor = Processor::parse(SRC).unwrap();
+    processor.link("display1", Display::new(2));
+    processor.run(100);
+
+    let display = processor.display("display1").unwrap();
+    assert_eq!(
+        display.pixel(0, 0),
+        Some(Rgba {
+            r: 128,
+            g: 0,
+            b: 127,
+            a: 255
+        })
+    );
+    assert_eq!(display.pixel(2, 0), None);
+
+    // The top row comes first
+    let bytes = display.to_rgba_bytes();
+    assert_eq!(bytes[8..], [128, 0, 127, 255, 0, 0, 255, 255]);
+    assert_eq!(bytes[..8], [0, 0, 255, 255, 0, 255, 0, 255]);
+
+    let mut ppm = Vec::new();
+    display.write_ppm(&mut ppm).unwrap();
+    assert_eq!(ppm[..11], *b"P6\n2 2\n255\n");
+    assert_eq!(ppm[11..17], [0, 0, 255, 0, 255, 0]);
+}
+
+#[test]
+fn poly_rotation() {
+    let mut processor =
+        Processor::parse("draw poly 40 40 6 10 30\ndraw linePoly 1 2 3 4 a").unwrap();
+    processor.set("a", Value::Number(45.));
+    processor.step();
+    processor.step();
+    assert_eq!(
+        processor.graphics_buffer(),
+        [
+            DrawCommand::Poly(40, 40, 6, 10, 30),
+            DrawCommand::LinePoly(1, 2, 3, 4, 45)
+        ]
+    );
+}
+
+#[test]
+fn huge_draw_operands() {
+    // Numbers that saturate when packed don't overflow
+    let mut processor = Processor::parse("draw rect -1e10 0 5 5\ndraw scale -1e12 1").unwrap();
+    processor.run(2);
+    assert_eq!(
+        processor.graphics_buffer(),
+        [DrawCommand::Rect(0, 0, 5, 5), DrawCommand::Scale(0., 1.)]
+    );
+}
+
+#[test]
+fn graphics_buffer_limit() {
+    let mut processor = Processor::parse("draw rect 0 0 1 1\njump 0 always").unwrap();
+    processor.run(600);
+    assert_eq!(processor.graphics_buffer().len(), 256);
+}
+
+/// A world with one flare, a core and a door, which records what it's told to do
+#[derive(Default)]
+struct Outpost {
+    flare: (f64, f64),
+    commands: Vec<UnitCommand>,
+    controls: Vec<(Value, ControlCommand)>,
+    radars: Vec<RadarQuery>,
+}
+
+impl World for Outpost {
+    fn sensor(&mut self, target: &Value, property: &Value) -> Value {
+        match (target, property) {
+            (Value::Unit(1), Value::Content(x)) if x == "x" => Value::Number(self.flare.0),
+            (Value::Unit(1), Value::Content(x)) if x == "y" => Value::Number(self.flare.1),
+            _ => Value::Null,
+        }
+    }
+
+    fn radar(&mut self, query: &RadarQuery) -> Value {
+        self.radars.push(query.clone());
+        Value::Unit(2)
+    }
+
+    fn link(&mut self, index: usize) -> Option<Value> {
+        (index == 0).then(|| Value::Building("door1".into()))
+    }
+
+    fn unit_bind(&mut self, unit_type: &Value) -> Value {
+        match unit_type {
+            Value::Content(x) if x == "flare" => Value::Unit(1),
+            _ => Value::Null,
+        }
+    }
+
+    fn unit_control(&mut self, _unit: &Value, command: &UnitCommand) -> Vec<Value> {
+        self.commands.push(command.clone());
+        match *command {
+            // The flare only gets halfway there each time
+            UnitCommand::Move { x, y } => {
+                self.flare = ((self.flare.0 + x) / 2., (self.flare.1 + y) / 2.);
+                Vec::new()
+            }
+            UnitCommand::Within { x, y, radius } => {
+                let distance = (self.flare.0 - x).hypot(self.flare.1 - y);
+                vec![Value::bool(distance < radius)]
+            }
+            _ => Vec::new(),
+        }
+    }
+
+    fn unit_locate(&mut self, _unit: &Value, query: &LocateQuery) -> Option<Located> {
+        (query.find == "building" && query.group == "core" && !query.enemy).then(|| Located {
+            x: 10.,
+            y: 20.,
+            building: Value::Building("core".into()),
+        })
+    }
+
+    fn control(&mut self, block: &Value, command: &ControlCommand) {
+        self.controls.push((block.clone(), command.clone()));
+    }
+}
+
+#[test]
+fn units() {
+    const SRC: &str = r#"
+        ucontrol move 5 5 0 0 0
+        ubind @flare
+        ulocate building core false @copper x y found core
+        ulocate ore core false @copper ore_x ore_y ore_found ore
+        ucontrol itemDrop core 10 0 0 0
+        uradar enemy flying any health 0 1 target
+        end
+    "#;
+    let mut processor = Processor::parse(SRC)
+        .unwrap()
+        .with_world(Outpost::default());
+    assert_eq!(processor.run(1000), Step::End);
+
+    assert_eq!(processor.get("@unit"), Value::Unit(1));
+    assert_eq!(processor.get("found"), Value::Number(1.));
+    assert_eq!(processor.get("x"), Value::Number(10.));
+    assert_eq!(processor.get("y"), Value::Number(20.));
+    assert_eq!(processor.get("core"), Value::Building("core".into()));
+    assert_eq!(processor.get("ore_found"), Value::Number(0.));
+    assert_eq!(processor.get("ore_x"), Value::Null);
+    assert_eq!(processor.get("target"), Value::Unit(2));
+
+    // Nothing is controlled before a unit is bound
+    let world = processor.world();
+    assert_eq!(
+        world.commands,
+        [UnitCommand::ItemDrop {
+            to: Value::Building("core".into()),
+            amount: 10.,
+        }]
+    );
+    assert_eq!(
+        world.radars,
+        [RadarQuery {
+            from: Value::Unit(1),
+            targets: ["enemy".into(), "flying".into(), "any".into()],
+            sort: "health".into(),
+            descending: true,
+        }]
+    );
+}
+
+#[test]
+fn unit_within() {
+    const SRC: &str = r#"
+        ubind @flare
+        fly:
+            ucontrol move 10 20 0 0 0
+            ucontrol within 10 20 1 near 0
+        jump fly equal near false
+        sensor x @unit @x
+        end
+    "#;
+    let mut processor = Processor::parse(SRC)
+        .unwrap()
+        .with_world(Outpost::default());
+    assert_eq!(processor.run(1000), Step::End);
+    assert_eq!(processor.get("near"), Value::Number(1.));
+
+    let world = processor.world();
+    let x = processor.get("x").num();
+    assert!((x - 10.).abs() < 1. && x == world.flare.0);
+    let moves = world
+        .commands
+        .iter()
+        .filter(|x| matches!(x, UnitCommand::Move { .. }))
+        .count();
+    assert_eq!(moves, 5);
+}
+
+#[test]
+fn control() {
+    const SRC: &str = r#"
+        getlink door 0
+        getlink missing 1
+        control enabled door false 0 0 0
+        end
+    "#;
+    let mut processor = Processor::parse(SRC)
+        .unwrap()
+        .with_world(Outpost::default());
+    assert_eq!(processor.run(1000), Step::End);
+
+    assert_eq!(processor.get("door"), Value::Building("door1".into()));
+    assert_eq!(processor.get("missing"), Value::Null);
+    assert_eq!(
+        processor.world().controls,
+        [(
+            Value::Building("door1".into()),
+            ControlCommand::Enabled(false)
+        )]
+    );
+}
+
+#[test]
+fn links_count() {
+    const SRC: &str = r#"
+        set n 0
+        loop:
+            getlink cell n
+            write n cell 0
+            op add n n 1
+        jump loop lessThan n @links
+        stop
+    "#;
+    let mut processor = Processor::parse(SRC).unwrap();
+    for name in ["cell1", "cell2", "cell3"] {
+        processor.link(name, Memory::cell());
+    }
+    assert_eq!(processor.get("@links"), Value::Number(3.));
+
+    assert_eq!(processor.run(100), Step::Stopped);
+    assert_eq!(processor.get("n"), Value::Number(3.));
+    assert_eq!(processor.memory("cell3").unwrap().read(0.), 2.);
+}
+
+#[test]
+fn switch() {
+    const SRC: &str = r#"
+        sensor before switch1 @enabled
+        control enabled switch1 false 0 0 0
+        sensor after switch1 @enabled
+        stop
+    "#;
+    let mut processor = Processor::parse(SRC)
+        .unwrap()
+        .with_world(Outpost::default());
+    processor.link("switch1", Block::Switch(true));
+    assert_eq!(processor.run(100), Step::Stopped);
+    assert_eq!(processor.get("before"), Value::Number(1.));
+    assert_eq!(processor.get("after"), Value::Number(0.));
+    assert_eq!(processor.block("switch1"), Some(&Block::Switch(false)));
+    // Linked switches don't go through the world
+    assert_eq!(processor.world().controls, []);
+}
+
+/// A world that counts the units spawned in it
+#[derive(Default)]
+struct Arena {
+    units: u32,
+}
+
+impl World for Arena {
+    fn apply(&mut self, event: &WorldEvent) -> Value {
+        match event {
+            WorldEvent::SpawnUnit { .. } => {
+                self.units += 1;
+                Value::Unit(self.units)
+            }
+            _ => Value::Null,
+        }
+    }
+
+    fn query(&mut self, query: &WorldQuery) -> Value {
+        match query {
+            WorldQuery::GetBlock { layer, .. } if layer == "floor" => {
+                Value::Content("stone".into())
+            }
+            WorldQuery::Fetch { kind, .. } if kind == "unitCount" => {
+                Value::Number(self.units.into())
+            }
+            _ => Value::Null,
+        }
+    }
+}
+
+#[test]
+fn wproc_blocks() {
+    const SRC: &str = r#"
+        getblock floor floor 4 5
+        setblock block @router 4 5 @sharded 1
+        end
+    "#;
+    let mut processor = Processor::parse_wproc(SRC)
+        .unwrap()
+        .with_world(Recorder::new(Arena::default()));
+    assert_eq!(processor.run(100), Step::End);
+
+    assert_eq!(processor.get("floor"), Value::Content("stone".into()));
+    assert_eq!(
+        processor.world().events(),
+        [WorldEvent::SetBlock {
+            layer: "block".into(),
+            x: 4.,
+            y: 5.,
+            block: Value::Content("router".into()),
+            team: Value::Content("sharded".into()),
+            rotation: 1.,
+        }]
+    );
+}
+
+#[test]
+fn wproc_units() {
+    const SRC: &str = r#"
+        spawn:
+            spawn @dagger 10 10 0 @crux last
+            fetch unitCount count @crux 0 @dagger 0
+        jump spawn lessThan count 2
+        end
+    "#;
+    let mut processor = Processor::parse_wproc(SRC)
+        .unwrap()
+        .with_world(Recorder::new(Arena::default()));
+    assert_eq!(processor.run(100), Step::End);
+    assert_eq!(processor.get("last"), Value::Unit(2));
+
+    let dagger = WorldEvent::SpawnUnit {
+        unit_type: Value::Content("dagger".into()),
+        x: 10.,
+        y: 10.,
+        rotation: 0.,
+        team: Value::Content("crux".into()),
+    };
+    assert_eq!(processor.world().events(), [dagger.clone(), dagger]);
+}
+
+#[test]
+fn wproc_events() {
+    const SRC: &str = r#"
+        effect shootBig 10 10 45 %ff0000 0
+        setrule unitHealth 2 @crux 0 0 0
+        setrule mapArea 0 1 2 30 40
+        print "Wave {0}"
+        format 2
+        message announce 3 @wait
+        end
+    "#;
+    let mut processor = Processor::parse_wproc(SRC)
+        .unwrap()
+        .with_world(Recorder::new(Arena::default()));
+    assert_eq!(processor.run(100), Step::End);
+
+    assert_eq!(processor.text_buffer(), "");
+    assert_eq!(
+        processor.world().events(),
+        [
+            WorldEvent::Effect {
+                effect: "shootBig".into(),
+                x: 10.,
+                y: 10.,
+                rotation: 45.,
+                colour: f64::from_bits(0xff00_00ff),
+                data: Value::Null,
+            },
+            WorldEvent::SetRule {
+                rule: "unitHealth".into(),
+                value: Value::Number(2.),
+                team: Value::Content("crux".into()),
+            },
+            WorldEvent::SetMapArea {
+                x: 1.,
+                y: 2.,
+                w: 30.,
+                h: 40.,
+            },
+            WorldEvent::Message {
+                kind: "announce".into(),
+                duration: 3.,
+                text: "Wave 2".into(),
+            }
+        ]
+    );
+}
+
+#[test]
+fn instructions_per_tick() {
+    for (kind, ipt) in [
+        (ProcessorKind::Micro, 2.),
+        (ProcessorKind::Logic, 8.),
+        (ProcessorKind::Hyper, 25.),
+    ] {
+        let mut processor = Processor::parse("op add i i 1").unwrap().with_kind(kind);
+        processor.advance(10);
+        assert_eq!(processor.get("i"), Value::Number(ipt * 10.));
+        assert_eq!(processor.get("@ipt"), Value::Number(ipt));
+    }
+
+    // Fractions are saved up between ticks
+    let mut processor = Processor::parse("op add i i 1").unwrap();
+    processor.set_ipt(0.25);
+    processor.advance(10);
+    assert_eq!(processor.get("i"), Value::Number(2.));
+
+    // Huge rates are clamped, instead of never finishing a tick
+    processor.set_ipt(f64::INFINITY);
+    processor.advance(1);
+    assert_eq!(processor.get("i"), Value::Number(2. + MAX_IPT));
+}
+
+#[test]
+fn setrate() {
+    let mut processor = Processor::parse_wproc("setrate 100\nop add i i 1").unwrap();
+    processor.advance(2);
+    assert_eq!(processor.ipt(), 100.);
+    assert_eq!(processor.get("i"), Value::Number(54.));
+
+    let mut processor = Processor::parse_wproc("setrate 1e300\nop add i i 1").unwrap();
+    processor.advance(2);
+    assert_eq!(processor.ipt(), MAX_IPT);
+}
+
+#[test]
+fn wait() {
+    const SRC: &str = r#"
+        jump cooling lessThan @time ready
+            op add shots shots 1
+            op add ready @time 750
+        cooling:
+        set tick @tick
+        wait 0.5
+        op add polls polls 1
+        wait 0
+    "#;
+
+    // Instructions run on their own don't move the clock
+    let mut processor = Processor::parse(SRC).unwrap();
+    assert_eq!(processor.run(100), Step::Waiting);
+    assert_eq!(processor.counter(), 4);
+    assert_eq!(processor.get("@tick"), Value::Number(0.));
+
+    let mut processor = Processor::parse(SRC).unwrap();
+    processor.advance(30);
+    assert_eq!(processor.get("polls"), Value::Null);
+    processor.advance(1);
+    assert_eq!(processor.get("polls"), Value::Number(1.));
+    assert_eq!(processor.get("tick"), Value::Number(30.));
+    assert_eq!(processor.get("@time"), Value::Number(31. / 60. * 1000.));
+
+    processor.advance(600);
+    assert_eq!(processor.tick(), 631);
+    assert_eq!(processor.get("@second"), Value::Number(631. / 60.));
+    assert_eq!(processor.get("polls"), Value::Number(21.));
+    // One shot every 750ms, checked every 500ms
+    assert_eq!(processor.get("shots"), Value::Number(11.));
+}
+
+#[test]
+fn shared_blocks() {
+    // Two processors increment the same slot without any locking, a world processor prints it,
+    // and another processor turns off a switch once it reaches 100
+    const INCREMENT: &str = r#"
+        read x cell1 0
+        op add x x 1
+        write x cell1 0
+    "#;
+    const WATCHER: &str = r#"
+        sensor enabled switch1 @enabled
+        read total bank1 0
+        print total
+        printflush message1
+        setflag "busy" enabled
+    "#;
+    const LIMIT: &str = r#"
+        read total cell1 0
+        op lessThan on total 100
+        control enabled switch1 on 0 0 0
+    "#;
+
+    let mut sim = Simulation::new();
+    sim.add_block("cell", Memory::cell());
+    sim.add_block("message", Block::message());
+    sim.add_block("switch", Block::Switch(true));
+
+    let a = sim.add_processor(Processor::parse(INCREMENT).unwrap());
+    let b = sim.add_processor(Processor::parse(INCREMENT).unwrap());
+    let watcher = sim.add_processor(Processor::parse_wproc(WATCHER).unwrap());
+    let limit = sim.add_processor(Processor::parse(LIMIT).unwrap());
+    sim.link(a, "cell1", "cell");
+    sim.link(b, "cell1", "cell");
+    sim.link(watcher, "switch1", "switch");
+    sim.link(watcher, "bank1", "cell");
+    sim.link(watcher, "message1", "message");
+    sim.link(limit, "cell1", "cell");
+    sim.link(limit, "switch1", "switch");
+
+    sim.run_tick();
+    // Each processor runs two whole increments, and reads 2 and 4 for the third
+    assert_eq!(sim.memory("cell").unwrap().read(0.), 4.);
+    assert_eq!(sim.message("message"), Some("4"));
+    // Processors see blocks by their own names, even between ticks
+    assert_eq!(
+        sim.processor(watcher).get("bank1"),
+        Value::Building("bank1".into())
+    );
+    assert_eq!(sim.processor(watcher).block("bank1"), None);
+
+    sim.run_tick();
+    // `a` starts by writing 3 over the 4 from `b`, so that increment is lost
+    assert_eq!(sim.processor(a).get("x"), Value::Number(5.));
+    assert_eq!(sim.memory("cell").unwrap().read(0.), 7.);
+
+    sim.advance(8);
+    assert_eq!(sim.block("switch"), Some(&Block::Switch(true)));
+    assert_eq!(sim.processor(watcher).get("enabled"), Value::Number(1.));
+
+    sim.advance(90);
+    assert_eq!(sim.tick(), 100);
+    assert_eq!(sim.processor(watcher).get("@tick"), Value::Number(100.));
+    // Without the races, each processor would have added 800 / 3, for 533 in total
+    assert_eq!(sim.memory("cell").unwrap().read(0.), 334.);
+    assert_eq!(sim.message("message"), Some("334"));
+    assert_eq!(sim.block("switch"), Some(&Block::Switch(false)));
+    assert_eq!(sim.processor(watcher).get("enabled"), Value::Number(0.));
+}
+
+#[test]
+fn relink_block() {
+    const SRC: &str = r#"
+        write 1 cell1 0
+        write 2 cell2 0
+        stop
+    "#;
+    let mut sim = Simulation::new();
+    sim.add_block("cell", Memory::cell());
+    let processor = sim.add_processor(Processor::parse(SRC).unwrap());
+    sim.link(processor, "cell1", "cell");
+    sim.link(processor, "cell2", "cell");
+    sim.advance(2);
+    assert_eq!(sim.memory("cell").unwrap().read(0.), 2.);
+    assert_eq!(
+        sim.processor(processor).get("cell1"),
+        Value::Building("cell1".into())
+    );
+}
+
+#[test]
+fn unresolved_link() {
+    const SRC: &str = r#"
+        print "lost"
+        printflush message1
+        getlink first 0
+        set message1 5
+        stop
+    "#;
+    let mut sim = Simulation::new();
+    let processor = sim.add_processor(Processor::parse(SRC).unwrap());
+    sim.link(processor, "message1", "missing");
+    sim.run_tick();
+
+    // The name is linked, but there's no block behind it to print to
+    let processor = sim.processor(processor);
+    assert_eq!(processor.block("message1"), None);
+    assert_eq!(processor.text_buffer(), "");
+    assert_eq!(processor.get("first"), Value::Building("message1".into()));
+    assert_eq!(
+        processor.get("message1"),
+        Value::Building("message1".into())
+    );
+}
+
+#[test]
+fn late_block() {
+    let mut sim = Simulation::new();
+    let processor = sim.add_processor(Processor::parse("write 1 cell1 0").unwrap());
+    sim.link(processor, "cell1", "late");
+    sim.run_tick();
+    sim.add_block("late", Memory::cell());
+    sim.run_tick();
+    assert_eq!(sim.memory("late").unwrap().read(0.), 1.);
+}
+
+#[test]
+fn debugger_breakpoints() {
+    const SRC: &str = r#"
+        set i 0
+        loop:
+            write i cell1 i
+            op add i i 1
+        jump loop lessThan i 4
+        print "done"
+        stop
+    "#;
+    let mut debugger = Debugger::parse(SRC).unwrap();
+    debugger.processor_mut().link("cell1", Memory::cell());
+
+    assert_eq!(debugger.line(0), Some(2));
+    assert_eq!(debugger.index_of_line(3), Some(1));
+    assert_eq!(debugger.add_line_breakpoint(6), Some(3));
+    assert_eq!(debugger.add_label_breakpoint("loop"), Some(1));
+    assert_eq!(debugger.add_label_breakpoint("missing"), None);
+    assert!(!debugger.add_breakpoint(10));
+    assert_eq!(debugger.breakpoints().collect::<Vec<_>>(), [1, 3]);
+
+    assert!(debugger.remove_breakpoint(1));
+    assert_eq!(debugger.step(), Pause::Step(Step::Continue));
+    assert_eq!(debugger.processor().counter(), 1);
+
+    assert_eq!(debugger.run(100), Pause::Breakpoint(3));
+    assert_eq!(debugger.eval("i"), Value::Number(1.));
+
+    // Continuing runs the instruction with the breakpoint before stopping again
+    assert_eq!(debugger.run(100), Pause::Breakpoint(3));
+    assert_eq!(debugger.eval("i"), Value::Number(2.));
+
+    assert!(debugger.remove_breakpoint(3));
+    assert_eq!(debugger.run(100), Pause::Step(Step::Stopped));
+    assert_eq!(debugger.processor().text_buffer(), "done");
+}
+
+#[test]
+fn debugger_watches() {
+    const SRC: &str = r#"
+        set i 0
+        loop:
+            write i cell1 i
+            op add i i 1
+        jump loop lessThan i 4
+    "#;
+    let mut debugger = Debugger::parse(SRC).unwrap();
+    debugger.processor_mut().link("cell1", Memory::cell());
+
+    debugger.watch("cell1[2]");
+    assert_eq!(
+        debugger.run(100),
+        Pause::Watch {
+            expression: "cell1[2]".into(),
+            old: Value::Number(0.),
+            new: Value::Number(2.),
+        }
+    );
+    assert_eq!(debugger.processor().counter(), 2);
+
+    assert!(debugger.unwatch("cell1[2]"));
+    debugger.watch("@counter");
+    assert_eq!(
+        debugger.step(),
+        Pause::Watch {
+            expression: "@counter".into(),
+            old: Value::Number(2.),
+            new: Value::Number(3.),
+        }
+    );
+    assert!(debugger.unwatch("@counter"));
+    assert!(!debugger.unwatch("@counter"));
+    assert_eq!(debugger.step(), Pause::Step(Step::Continue));
+}
+
+#[test]
+fn debugger_advance() {
+    // Counts every half a second
+    const SRC: &str = r#"
+        set tick @tick
+        wait 0.5
+        op add blinks blinks 1
+    "#;
+    let mut debugger = Debugger::parse(SRC).unwrap();
+    assert_eq!(debugger.advance(61), Pause::Step(Step::Continue));
+
+    let mut processor = Processor::parse(SRC).unwrap();
+    processor.advance(61);
+    assert_eq!(debugger.processor().tick(), 61);
+    assert_eq!(debugger.eval("blinks"), Value::Number(2.));
+    assert_eq!(debugger.eval("tick"), processor.get("tick"));
+
+    let mut debugger = Debugger::parse(SRC).unwrap();
+    debugger.add_breakpoint(2);
+    debugger.watch("blinks");
+
+    // The breakpoint is reached once the wait finishes, partway through tick 30
+    assert_eq!(debugger.advance(100), Pause::Breakpoint(2));
+    assert_eq!(debugger.processor().tick(), 30);
+    assert_eq!(debugger.eval("blinks"), Value::Null);
+
+    debugger.remove_breakpoint(2);
+    assert_eq!(
+        debugger.advance(70),
+        Pause::Watch {
+            expression: "blinks".into(),
+            old: Value::Null,
+            new: Value::Number(1.),
+        }
+    );
+    assert_eq!(debugger.processor().tick(), 30);
+
+    // The paused tick is finished first, so this ends on the same tick as running straight
+    // through would
+    debugger.unwatch("blinks");
+    assert_eq!(debugger.advance(70), Pause::Step(Step::Continue));
+    assert_eq!(debugger.processor().tick(), 100);
+
+    let mut processor = Processor::parse(SRC).unwrap();
+    processor.advance(100);
+    assert_eq!(debugger.eval("blinks"), processor.get("blinks"));
+    assert_eq!(debugger.processor().counter(), processor.counter());
+}
+
+#[test]
+fn traces() {
+    // Both write 0, 2 and 4, one with a loop and one without
+    const SLOW: &str = r#"
+        set i 0
+        loop:
+            op mul x i 2
+            write x cell1 i
+            op add i i 1
+        jump loop lessThan i 3
+        printflush message1
+        wait 0.5
+    "#;
+    const FAST: &str = r#"
+        write 0 cell1 0
+        write 2 cell1 1
+        write 4 cell1 2
+        printflush message1
+        wait 0.5
+    "#;
+    let trace = |src: &str| {
+        let mut processor = Processor::parse(src).unwrap();
+        processor.link("cell1", Memory::cell());
+        processor.link("message1", Block::message());
+        processor.start_trace();
+        processor.advance(60);
+        processor.take_trace().unwrap()
+    };
+
+    let slow = trace(SLOW);
+    let step = &slow.steps()[5];
+    assert_eq!((step.index, step.counter, step.tick), (1, 2, 0));
+    assert_eq!(step.writes, [("x".to_string(), Value::Number(2.))]);
+    // A `wait` that isn't done stays on the same instruction
+    let last = slow.steps().last().unwrap();
+    assert_eq!((last.index, last.counter, last.tick), (6, 6, 59));
+
+    let fast = trace(FAST);
+    assert!(slow.effects().eq(fast.effects()));
+    assert_eq!(
+        fast.effects().nth(3),
+        Some(&Effect::PrintFlush {
+            output: Value::Building("message1".into()),
+            text: String::new(),
+        })
+    );
+}
+
+#[test]
+fn replay() {
+    const SRC: &str = r#"
+        set i 0
+        loop:
+            op mul x i 2
+            write x cell1 i
+            op add i i 1
+        jump loop lessThan i 3
+        wait 0.5
+    "#;
+    let mut processor = Processor::parse(SRC).unwrap();
+    processor.link("cell1", Memory::cell());
+    processor.start_trace();
+    processor.advance(60);
+    let trace = processor.take_trace().unwrap();
+
+    let mut fresh = Processor::parse(SRC).unwrap();
+    fresh.link("cell1", Memory::cell());
+    assert_eq!(trace.replay(&mut fresh), Ok(()));
+    // Replaying doesn't leave a trace behind
+    assert!(fresh.trace().is_none());
+    assert_eq!(Trace::default().replay(&mut fresh), Ok(()));
+
+    // Replaying after a change finds where it went wrong
+    let src = SRC.replace("op mul", "op add");
+    let mut changed = Processor::parse(&src).unwrap();
+    changed.link("cell1", Memory::cell());
+    let divergence = trace.replay(&mut changed).unwrap_err();
+    assert_eq!(divergence.step, 1);
+    assert_eq!(
+        divergence.found.unwrap().writes,
+        [("x".to_string(), Value::Number(2.))]
+    );
+}
+
+#[test]
+fn profile() {
+    const SRC: &str = r#"
+        set i 0
+        outer:
+            set j 0
+        inner:
+            op add j j 1
+            jump inner lessThan j 3
+        op add i i 1
+        jump outer notEqual i 2
+        done:
+        stop
+    "#;
+    let program = Program::<Statement>::parse(SRC).unwrap();
+    let labels = program.labels().clone();
+    let mut processor = Processor::from(program).with_kind(ProcessorKind::Hyper);
+    processor.start_profile();
+    processor.advance(10);
+    let profile = processor.take_profile().unwrap();
+
+    assert_eq!(profile.hits(2), 6);
+    // A processor that has stopped runs its `stop` again every tick
+    assert_eq!(profile.hits(6), 10);
+    assert_eq!(profile.hits(7), 0);
+    assert_eq!(profile.branch(3), Some((4, 2)));
+    assert_eq!(profile.branch(5), Some((1, 1)));
+    assert_eq!(profile.branch(0), None);
+
+    let report = profile.report(processor.statements(), &labels);
+    assert_eq!(report.hits, 29);
+    let ranges: Vec<_> = report
+        .labels
+        .iter()
+        .map(|x| (&*x.label, x.range.clone(), x.hits))
+        .collect();
+    assert_eq!(
+        ranges,
+        [("outer", 1..2, 2), ("inner", 2..6, 16), ("done", 6..7, 10)]
+    );
+
+    let text = report.to_string();
+    let lines: Vec<_> = text.lines().collect();
+    assert_eq!(
+        lines[4],
+        "    3          6       0.24             4/2  jump 2 lessThan j 3"
+    );
+    assert_eq!(lines[11], "       2..6         16       0.64  inner");
+    assert_eq!(lines[13], "total: 29 instructions in 1.16 ticks");
+
+    assert_eq!(profile.report(&[] as &[Statement], &HashMap::new()).hits, 0);
+    assert_eq!(
+        Profile::new(2)
+            .report(processor.statements(), &labels)
+            .ticks,
+        0.
+    );
+}
+
+#[test]
+fn snapshots() {
+    // Draws and prints random numbers, waiting between them
+    const SRC: &str = r#"
+        op rand x 100
+        write x cell1 0
+        draw rect x 0 1 1
+        print x
+        wait 0.25
+    "#;
+    let mut processor = Processor::parse(SRC).unwrap();
+    processor.link("cell1", Memory::cell());
+    processor.advance(20);
+    let checkpoint = processor.snapshot();
+
+    assert_eq!(checkpoint.tick, 20);
+    assert_eq!(checkpoint.counter, 4);
+    assert_eq!(checkpoint.graphics_buffer.len(), 2);
+    assert_eq!(checkpoint.waits.len(), 1);
+    assert_eq!(checkpoint.links[0].0, "cell1");
+
+    let mut restored = Processor::parse(SRC).unwrap();
+    restored.restore(checkpoint.clone());
+    assert_eq!(restored.snapshot(), checkpoint);
+    assert_eq!(
+        restored.memory("cell1").unwrap().read(0.),
+        restored.get("x").num()
+    );
+
+    // Picking up from the checkpoint keeps the clock, the random numbers and the wait going
+    processor.start_trace();
+    processor.advance(40);
+    restored.start_trace();
+    restored.advance(40);
+    assert_eq!(restored.take_trace(), processor.take_trace());
+    assert_eq!(restored.snapshot(), processor.snapshot());
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn snapshot_serde() {
+    const SRC: &str = r#"
+        op rand x 100
+        write x cell1 0
+        draw rect x 0 1 1
+        print x
+        wait 0.25
+    "#;
+    let mut processor = Processor::parse(SRC).unwrap();
+    processor.link("cell1", Memory::cell());
+    processor.advance(20);
+    let json = serde_json::to_string(&processor.snapshot()).unwrap();
+
+    processor.start_trace();
+    processor.advance(40);
+    let trace = serde_json::to_string(&processor.take_trace().unwrap()).unwrap();
+
+    // A saved snapshot picks up the same way as the original
+    let mut restored = Processor::parse(SRC).unwrap();
+    restored.restore(serde_json::from_str(&json).unwrap());
+    restored.start_trace();
+    restored.advance(40);
+    let trace: Trace = serde_json::from_str(&trace).unwrap();
+    assert_eq!(restored.take_trace().unwrap(), trace);
+    assert_eq!(restored.snapshot(), processor.snapshot());
+}
@@ -0,0 +1,188 @@
+use crate::vm::display::Display;
+
+/// A block that a processor can be linked to
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Block {
+    /// A memory cell or memory bank
+    Memory(Memory),
+    /// A message block, holding the text that was last flushed to it
+    Message(String),
+    /// A logic display
+    Display(Display),
+    /// A switch, which `control enabled` turns on and off and `sensor @enabled` reads
+    Switch(bool),
+}
+
+impl Block {
+    /// Gets the memory, if this is a memory block
+    #[must_use]
+    pub fn as_memory(&self) -> Option<&Memory> {
+        match self {
+            Self::Memory(x) => Some(x),
+            _ => None,
+        }
+    }
+
+    /// Gets a mutable reference to the memory, if this is a memory block
+    pub fn as_memory_mut(&mut self) -> Option<&mut Memory> {
+        match self {
+            Self::Memory(x) => Some(x),
+            _ => None,
+        }
+    }
+
+    /// Creates an empty message block
+    #[must_use]
+    pub fn message() -> Self {
+        Self::Message(String::new())
+    }
+
+    /// Gets the display, if this is a logic display
+    #[must_use]
+    pub fn as_display(&self) -> Option<&Display> {
+        match self {
+            Self::Display(x) => Some(x),
+            _ => None,
+        }
+    }
+
+    /// Gets a mutable reference to the display, if this is a logic display
+    pub fn as_display_mut(&mut self) -> Option<&mut Display> {
+        match self {
+            Self::Display(x) => Some(x),
+            _ => None,
+        }
+    }
+
+    /// Gets the text of a message block
+    #[must_use]
+    pub fn as_message(&self) -> Option<&str> {
+        match self {
+            Self::Message(x) => Some(x),
+            _ => None,
+        }
+    }
+
+    /// Gets whether a switch is on, if this is a switch
+    #[must_use]
+    pub fn as_switch(&self) -> Option<bool> {
+        match self {
+            Self::Switch(x) => Some(*x),
+            _ => None,
+        }
+    }
+
+    /// Gets a mutable reference to whether a switch is on, if this is a switch
+    pub fn as_switch_mut(&mut self) -> Option<&mut bool> {
+        match self {
+            Self::Switch(x) => Some(x),
+            _ => None,
+        }
+    }
+}
+
+impl From<Memory> for Block {
+    fn from(x: Memory) -> Self {
+        Self::Memory(x)
+    }
+}
+
+impl From<Display> for Block {
+    fn from(x: Display) -> Self {
+        Self::Display(x)
+    }
+}
+
+/// The memory of a memory cell or memory bank, which holds a fixed number of numbers that start
+/// out as 0.
+///
+/// # Examples
+///
+/// ```
+/// # use mlog_parse::vm::blocks::Memory;
+/// let mut cell = Memory::cell();
+/// cell.write(3., 5.);
+/// cell.write(64., 1.);
+///
+/// assert_eq!(cell.read(3.), 5.);
+/// assert_eq!(cell.read(64.), 0.);
+/// assert_eq!(cell.len(), 64);
+/// ```
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Memory {
+    values: Vec<f64>,
+}
+
+impl Memory {
+    /// The number of slots in a memory cell
+    pub const CELL_SIZE: usize = 64;
+    /// The number of slots in a memory bank
+    pub const BANK_SIZE: usize = 512;
+
+    /// Creates memory with the given number of slots
+    #[must_use]
+    pub fn new(size: usize) -> Self {
+        Self {
+            values: vec![0.; size],
+        }
+    }
+
+    /// Creates the memory of a memory cell
+    #[must_use]
+    pub fn cell() -> Self {
+        Self::new(Self::CELL_SIZE)
+    }
+
+    /// Creates the memory of a memory bank
+    #[must_use]
+    pub fn bank() -> Self {
+        Self::new(Self::BANK_SIZE)
+    }
+
+    /// Gets the number of slots
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    /// Checks if there are no slots
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+
+    /// Reads a slot like the `read` instruction does, giving 0 if it's out of range
+    #[must_use]
+    pub fn read(&self, index: f64) -> f64 {
+        Self::slot(index)
+            .and_then(|x| self.values.get(x))
+            .copied()
+            .unwrap_or(0.)
+    }
+
+    /// Writes to a slot like the `write` instruction does, doing nothing if it's out of range
+    pub fn write(&mut self, index: f64, value: f64) {
+        if let Some(slot) = Self::slot(index).and_then(|x| self.values.get_mut(x)) {
+            *slot = value;
+        }
+    }
+
+    /// Gets every slot
+    #[must_use]
+    pub fn values(&self) -> &[f64] {
+        &self.values
+    }
+
+    /// Gets every slot mutably, e.g. to fill the memory before running a program
+    pub fn values_mut(&mut self) -> &mut [f64] {
+        &mut self.values
+    }
+
+    /// Turns an index into a slot, truncating it like the game's int cast
+    fn slot(index: f64) -> Option<usize> {
+        let index = index as i32;
+        usize::try_from(index).ok()
+    }
+}
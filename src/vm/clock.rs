@@ -0,0 +1,45 @@
+/// The number of ticks in a second of game time
+pub const TICKS_PER_SECOND: f64 = 60.;
+
+/// The most instructions a processor can run every tick, which is as high as `setrate` goes in a
+/// world processor
+pub const MAX_IPT: f64 = 1000.;
+
+/// The most instructions a processor can save up while it's waiting, as a multiple of its
+/// instructions per tick
+pub(super) const MAX_ACCUMULATED_TICKS: f64 = 5.;
+
+/// A kind of processor block, which decides how many instructions it runs every tick
+///
+/// # Examples
+///
+/// ```
+/// # use mlog_parse::vm::clock::ProcessorKind;
+/// assert_eq!(ProcessorKind::Micro.ipt(), 2.);
+/// assert_eq!(ProcessorKind::default(), ProcessorKind::Logic);
+/// ```
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ProcessorKind {
+    /// A micro processor, running 2 instructions per tick
+    Micro,
+    /// A logic processor, running 8 instructions per tick
+    #[default]
+    Logic,
+    /// A hyper processor, running 25 instructions per tick
+    Hyper,
+    /// A world processor, running 8 instructions per tick unless changed with `setrate`
+    World,
+}
+
+impl ProcessorKind {
+    /// Gets the number of instructions run every tick
+    #[must_use]
+    pub fn ipt(self) -> f64 {
+        match self {
+            Self::Micro => 2.,
+            Self::Logic | Self::World => 8.,
+            Self::Hyper => 25.,
+        }
+    }
+}
@@ -0,0 +1,332 @@
+use super::world::{NoWorld, World};
+use super::{Processor, Step, Value};
+use crate::parser::{errs::ParseError, lexer::Lexer, span::Span, statements::Statement};
+use std::collections::{BTreeSet, HashMap};
+
+/// Why a [`Debugger`] stopped running the program
+#[derive(Debug, PartialEq, Clone)]
+pub enum Pause {
+    /// The processor stopped for its own reasons, like running out of steps or reaching a `stop`
+    Step(Step),
+    /// The next instruction to run has a breakpoint on it
+    Breakpoint(usize),
+    /// A watched expression changed
+    Watch {
+        /// The expression, as it was passed to [`Debugger::watch`]
+        expression: String,
+        /// The value before the last instruction ran
+        old: Value,
+        /// The value now
+        new: Value,
+    },
+}
+
+/// A debugger for a [`Processor`], which can step through a program, stop at breakpoints and
+/// watch for changes to variables.
+///
+/// Watched expressions are anything [`Processor::get`] accepts (like `x` or `@counter`), or a
+/// slot in a memory block written as `cell1[3]` or `cell1[i]`.
+///
+/// [`Debugger::run`] doesn't move the clock, so a program that `wait`s stays waiting. Use
+/// [`Debugger::advance`] to run it tick by tick instead.
+///
+/// # Examples
+///
+/// ```
+/// # use mlog_parse::vm::{Value, debug::{Debugger, Pause}};
+/// const SRC: &str = r#"
+///     set i 0
+///     loop:
+///         op add i i 1
+///         op mul square i i
+///     jump loop lessThan i 10
+/// "#;
+///
+/// let mut debugger = Debugger::parse(SRC).unwrap();
+/// assert_eq!(debugger.add_label_breakpoint("loop"), Some(1));
+/// debugger.watch("square");
+///
+/// assert_eq!(debugger.run(100), Pause::Breakpoint(1));
+/// assert_eq!(debugger.line(1), Some(4));
+///
+/// debugger.remove_breakpoint(1);
+/// debugger.processor_mut().set("i", Value::Number(4.));
+/// assert_eq!(
+///     debugger.run(100),
+///     Pause::Watch {
+///         expression: "square".into(),
+///         old: Value::Null,
+///         new: Value::Number(25.),
+///     }
+/// );
+/// ```
+#[derive(Debug, Clone)]
+pub struct Debugger<'a, W = NoWorld> {
+    processor: Processor<'a, W>,
+    spans: Vec<Span>,
+    labels: HashMap<&'a str, usize>,
+    breakpoints: BTreeSet<usize>,
+    watches: Vec<(String, Value)>,
+    /// Whether [`Self::advance`] paused partway through a tick
+    in_tick: bool,
+}
+
+impl<'a> Debugger<'a> {
+    /// Parses a program and creates a debugger for it, keeping where each statement is in the
+    /// source.
+    ///
+    /// # Errors
+    ///
+    /// Returns every error found in the source if there were any.
+    pub fn parse(src: &'a str) -> Result<Self, Vec<ParseError<'a>>> {
+        let parsed = Lexer::<Statement>::new(src).parse_all();
+        if !parsed.is_ok() {
+            return Err(parsed.errors);
+        }
+
+        let (statements, spans) = parsed
+            .statements
+            .into_iter()
+            .map(|x| (x.statement, x.span))
+            .unzip();
+
+        Ok(Self {
+            processor: Processor::new(statements),
+            spans,
+            labels: parsed.jump_labels,
+            breakpoints: BTreeSet::new(),
+            watches: Vec::new(),
+            in_tick: false,
+        })
+    }
+}
+
+impl<'a, W: World> Debugger<'a, W> {
+    /// Creates a debugger for a processor. Without the source, breakpoints can only be set by
+    /// index, and there are no lines to map them to.
+    #[must_use]
+    pub fn new(processor: Processor<'a, W>) -> Self {
+        Self {
+            processor,
+            spans: Vec::new(),
+            labels: HashMap::new(),
+            breakpoints: BTreeSet::new(),
+            watches: Vec::new(),
+            in_tick: false,
+        }
+    }
+
+    /// Replaces the world around the processor being debugged
+    #[must_use]
+    pub fn with_world<V: World>(self, world: V) -> Debugger<'a, V> {
+        Debugger {
+            processor: self.processor.with_world(world),
+            spans: self.spans,
+            labels: self.labels,
+            breakpoints: self.breakpoints,
+            watches: self.watches,
+            in_tick: self.in_tick,
+        }
+    }
+
+    /// Gets the processor being debugged, e.g. to look at its variables
+    #[must_use]
+    pub fn processor(&self) -> &Processor<'a, W> {
+        &self.processor
+    }
+
+    /// Gets the processor being debugged mutably, e.g. to change its variables or link blocks
+    pub fn processor_mut(&mut self) -> &mut Processor<'a, W> {
+        &mut self.processor
+    }
+
+    /// Gets the span in the source of the statement at `index`
+    #[must_use]
+    pub fn span(&self, index: usize) -> Option<Span> {
+        self.spans.get(index).copied()
+    }
+
+    /// Gets the line (1-based, like most editors) of the statement at `index`
+    #[must_use]
+    pub fn line(&self, index: usize) -> Option<usize> {
+        self.span(index).map(|x| x.line + 1)
+    }
+
+    /// Gets the index of the first statement on or after a line (1-based)
+    #[must_use]
+    pub fn index_of_line(&self, line: usize) -> Option<usize> {
+        self.spans.iter().position(|x| x.line + 1 >= line)
+    }
+
+    /// Adds a breakpoint before the statement at `index`. Returns false if there isn't one.
+    pub fn add_breakpoint(&mut self, index: usize) -> bool {
+        let exists = index < self.processor.statements().len();
+        if exists {
+            self.breakpoints.insert(index);
+        }
+        exists
+    }
+
+    /// Adds a breakpoint before the statement a label points to, returning its index
+    pub fn add_label_breakpoint(&mut self, label: &str) -> Option<usize> {
+        let index = *self.labels.get(label)?;
+        self.add_breakpoint(index).then_some(index)
+    }
+
+    /// Adds a breakpoint before the first statement on or after a line (1-based), returning its
+    /// index
+    pub fn add_line_breakpoint(&mut self, line: usize) -> Option<usize> {
+        let index = self.index_of_line(line)?;
+        self.add_breakpoint(index).then_some(index)
+    }
+
+    /// Removes a breakpoint, returning whether there was one
+    pub fn remove_breakpoint(&mut self, index: usize) -> bool {
+        self.breakpoints.remove(&index)
+    }
+
+    /// Gets the indices of every breakpoint, in order
+    pub fn breakpoints(&self) -> impl Iterator<Item = usize> + '_ {
+        self.breakpoints.iter().copied()
+    }
+
+    /// Starts watching an expression, so that running pauses whenever it changes
+    pub fn watch(&mut self, expression: impl Into<String>) {
+        let expression = expression.into();
+        if !self.watches.iter().any(|x| x.0 == expression) {
+            let value = self.eval(&expression);
+            self.watches.push((expression, value));
+        }
+    }
+
+    /// Stops watching an expression, returning whether it was watched
+    pub fn unwatch(&mut self, expression: &str) -> bool {
+        let len = self.watches.len();
+        self.watches.retain(|x| x.0 != expression);
+        self.watches.len() != len
+    }
+
+    /// Gets every watched expression along with its value
+    pub fn watches(&self) -> impl Iterator<Item = (&str, &Value)> {
+        self.watches.iter().map(|(k, v)| (k.as_str(), v))
+    }
+
+    /// Gets the value of an expression, in the same form as watches
+    #[must_use]
+    pub fn eval(&self, expression: &str) -> Value {
+        let slot = expression.strip_suffix(']').and_then(|x| x.split_once('['));
+
+        match slot {
+            Some((block, index)) => {
+                let index = self.processor.get(index).num();
+                match self.processor.get(block) {
+                    Value::Building(name) => self
+                        .processor
+                        .memory(&name)
+                        .map_or(Value::Null, |x| Value::number(x.read(index))),
+                    _ => Value::Null,
+                }
+            }
+            None => self.processor.get(expression),
+        }
+    }
+
+    /// Runs a single instruction, ignoring breakpoints
+    pub fn step(&mut self) -> Pause {
+        let step = self.processor.step();
+        self.check_watches().unwrap_or(Pause::Step(step))
+    }
+
+    /// Runs the processor for `ticks` ticks of game time like [`Processor::advance`], pausing the
+    /// same way as [`Self::run`]. Pausing leaves the processor partway through a tick, which is
+    /// finished first (and counted as one of the `ticks`) when this is called again. Returns
+    /// [`Step::Continue`] once every tick has run.
+    pub fn advance(&mut self, ticks: u64) -> Pause {
+        let mut ran = false;
+
+        for _ in 0..ticks {
+            if !self.in_tick {
+                self.processor.start_tick();
+                self.in_tick = true;
+            }
+
+            while self.in_tick && self.processor.has_saved_instruction() {
+                if ran && let Some(index) = self.next_breakpoint() {
+                    return Pause::Breakpoint(index);
+                }
+                ran = true;
+
+                let step = self.processor.step_saved();
+                // The rest of the tick is skipped, like in `Processor::run_tick`
+                if matches!(step, Step::Stopped | Step::Waiting) {
+                    self.end_tick();
+                }
+                if let Some(pause) = self.check_watches() {
+                    return pause;
+                }
+            }
+
+            if self.in_tick {
+                self.end_tick();
+            }
+        }
+        Pause::Step(Step::Continue)
+    }
+
+    /// Moves the clock on to the next tick
+    fn end_tick(&mut self) {
+        self.in_tick = false;
+        self.processor.set_tick(self.processor.tick() + 1);
+    }
+
+    /// Runs up to `max_steps` instructions, pausing before an instruction with a breakpoint or
+    /// after one that changes a watched expression. The instruction the processor is on always
+    /// runs, so that calling this again continues past a breakpoint.
+    pub fn run(&mut self, max_steps: usize) -> Pause {
+        for i in 0..max_steps {
+            if i > 0
+                && let Some(index) = self.next_breakpoint()
+            {
+                return Pause::Breakpoint(index);
+            }
+
+            match self.step() {
+                Pause::Step(Step::Continue) => {}
+                x => return x,
+            }
+        }
+        Pause::Step(Step::Continue)
+    }
+
+    /// Gets the breakpoint on the next instruction to run, if there is one
+    fn next_breakpoint(&self) -> Option<usize> {
+        let len = self.processor.statements().len();
+        // The counter wraps around before the next instruction runs
+        let index = if self.processor.counter() >= len {
+            0
+        } else {
+            self.processor.counter()
+        };
+        self.breakpoints.contains(&index).then_some(index)
+    }
+
+    /// Updates the values of watched expressions, returning the first one that changed
+    fn check_watches(&mut self) -> Option<Pause> {
+        let mut changed = None;
+
+        for i in 0..self.watches.len() {
+            let new = self.eval(&self.watches[i].0);
+            let (expression, value) = &mut self.watches[i];
+            // Compared strictly, so that changes between objects and numbers are seen
+            if !new.strict_eq(value) {
+                let old = std::mem::replace(value, new.clone());
+                changed.get_or_insert(Pause::Watch {
+                    expression: expression.clone(),
+                    old,
+                    new,
+                });
+            }
+        }
+        changed
+    }
+}
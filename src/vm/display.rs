@@ -0,0 +1,330 @@
+use crate::parser::args::Rgba;
+use std::io::{self, Write};
+
+/// A drawing command, queued by a `draw` instruction until `drawflush` sends it to a display.
+///
+/// Like in the game, numbers are truncated to integers before being queued, and coordinates are
+/// limited to the range -511 to 511.
+#[derive(Debug, PartialEq, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum DrawCommand {
+    /// Fills the whole display with a colour
+    Clear(Rgba),
+    /// Sets the colour of everything drawn after it
+    Colour(Rgba),
+    /// Sets the width of lines
+    Stroke(i32),
+    /// A line between two points
+    Line(i32, i32, i32, i32),
+    /// A filled rectangle, from its bottom left corner and size
+    Rect(i32, i32, i32, i32),
+    /// The outline of a rectangle, from its bottom left corner and size
+    LineRect(i32, i32, i32, i32),
+    /// A filled regular polygon, from its centre, number of sides, radius and rotation in degrees
+    Poly(i32, i32, i32, i32, i32),
+    /// The outline of a regular polygon
+    LinePoly(i32, i32, i32, i32, i32),
+    /// A filled triangle
+    Triangle(i32, i32, i32, i32, i32, i32),
+    /// Moves everything drawn after it
+    Translate(i32, i32),
+    /// Scales everything drawn after it
+    Scale(f64, f64),
+    /// Rotates everything drawn after it, in degrees
+    Rotate(i32),
+    /// Resets translation, scaling and rotation
+    Reset,
+}
+
+/// An affine transformation, as a 2x3 matrix
+#[derive(Debug, PartialEq, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+struct Transform([f64; 6]);
+
+impl Transform {
+    const IDENTITY: Self = Self([1., 0., 0., 1., 0., 0.]);
+
+    /// Applies `other` before this transformation
+    fn then(self, other: [f64; 6]) -> Self {
+        let [a, b, c, d, x, y] = self.0;
+        let [oa, ob, oc, od, ox, oy] = other;
+        Self([
+            a * oa + c * ob,
+            b * oa + d * ob,
+            a * oc + c * od,
+            b * oc + d * od,
+            a * ox + c * oy + x,
+            b * ox + d * oy + y,
+        ])
+    }
+
+    fn apply(self, (x, y): (f64, f64)) -> (f64, f64) {
+        let [a, b, c, d, tx, ty] = self.0;
+        (a * x + c * y + tx, b * x + d * y + ty)
+    }
+}
+
+/// A logic display, which draws the commands flushed to it into a framebuffer.
+///
+/// Shapes are filled wherever they cover the centre of a pixel, like on a GPU. Pixels centred
+/// exactly on an edge are drawn if the shape is below and to the left of them. Images and text
+/// (`draw image` and `draw print`) aren't drawn, since they need the game's sprites and fonts.
+///
+/// The origin is at the bottom left, like in the game, but exported images start from the top
+/// row like most image formats.
+///
+/// # Examples
+///
+/// ```
+/// # use mlog_parse::vm::{Processor, display::Display};
+/// # use mlog_parse::parser::args::Rgba;
+/// const SRC: &str = r#"
+///     draw clear 0 0 0
+///     draw color 255 0 0 255
+///     draw rect 10 10 20 20
+///     drawflush display1
+/// "#;
+///
+/// let mut processor = Processor::parse(SRC).unwrap();
+/// processor.link("display1", Display::logic());
+/// processor.run(4);
+///
+/// let display = processor.display("display1").unwrap();
+/// assert_eq!(display.pixel(15, 15), Some(Rgba { r: 255, g: 0, b: 0, a: 255 }));
+/// assert_eq!(display.pixel(5, 5), Some(Rgba { r: 0, g: 0, b: 0, a: 255 }));
+/// ```
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Display {
+    size: usize,
+    pixels: Vec<Rgba>,
+    colour: Rgba,
+    stroke: f64,
+    transform: Transform,
+}
+
+impl Display {
+    /// The size of a logic display
+    pub const LOGIC_SIZE: usize = 80;
+    /// The size of a large logic display
+    pub const LARGE_SIZE: usize = 176;
+
+    /// Creates a square display with the given size, filled with black
+    #[must_use]
+    pub fn new(size: usize) -> Self {
+        Self {
+            size,
+            pixels: vec![Rgba::default(); size * size],
+            colour: Rgba {
+                r: 255,
+                g: 255,
+                b: 255,
+                a: 255,
+            },
+            stroke: 1.,
+            transform: Transform::IDENTITY,
+        }
+    }
+
+    /// Creates a logic display (80x80)
+    #[must_use]
+    pub fn logic() -> Self {
+        Self::new(Self::LOGIC_SIZE)
+    }
+
+    /// Creates a large logic display (176x176)
+    #[must_use]
+    pub fn large() -> Self {
+        Self::new(Self::LARGE_SIZE)
+    }
+
+    /// Gets the width and height of the display
+    #[must_use]
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    /// Gets the colour of a pixel, with `(0, 0)` at the bottom left
+    #[must_use]
+    pub fn pixel(&self, x: usize, y: usize) -> Option<Rgba> {
+        (x < self.size && y < self.size).then(|| self.pixels[y * self.size + x])
+    }
+
+    /// Gets the framebuffer as RGBA bytes, starting from the top left
+    #[must_use]
+    pub fn to_rgba_bytes(&self) -> Vec<u8> {
+        self.pixels
+            .chunks(self.size.max(1))
+            .rev()
+            .flatten()
+            .flat_map(|x| [x.r, x.g, x.b, x.a])
+            .collect()
+    }
+
+    /// Writes the framebuffer as a binary PPM image, ignoring transparency.
+    ///
+    /// # Errors
+    ///
+    /// Returns any error from writing to `out`.
+    pub fn write_ppm(&self, mut out: impl Write) -> io::Result<()> {
+        write!(out, "P6\n{} {}\n255\n", self.size, self.size)?;
+        let rgb: Vec<_> = self
+            .to_rgba_bytes()
+            .chunks(4)
+            .flat_map(|x| [x[0], x[1], x[2]])
+            .collect();
+        out.write_all(&rgb)
+    }
+
+    /// Draws commands, like the display does after a `drawflush`
+    pub fn draw(&mut self, commands: &[DrawCommand]) {
+        for command in commands {
+            self.draw_command(*command);
+        }
+    }
+
+    fn draw_command(&mut self, command: DrawCommand) {
+        let f = f64::from;
+
+        match command {
+            DrawCommand::Clear(colour) => self.pixels.fill(colour),
+            DrawCommand::Colour(colour) => self.colour = colour,
+            DrawCommand::Stroke(width) => self.stroke = f(width),
+            DrawCommand::Line(x1, y1, x2, y2) => {
+                self.line((f(x1), f(y1)), (f(x2), f(y2)), true);
+            }
+            DrawCommand::Rect(x, y, w, h) => self.rect(f(x), f(y), f(w), f(h)),
+            DrawCommand::LineRect(x, y, w, h) => {
+                let (x, y, w, h, s) = (f(x), f(y), f(w), f(h), self.stroke);
+                self.rect(x, y, w, s);
+                self.rect(x, y + h, w, -s);
+                self.rect(x + w, y, -s, h);
+                self.rect(x, y, s, h);
+            }
+            DrawCommand::Poly(x, y, sides, radius, rotation) => {
+                let points = Self::poly_points(x, y, sides, radius, rotation);
+                self.fill(&points);
+            }
+            DrawCommand::LinePoly(x, y, sides, radius, rotation) => {
+                let points = Self::poly_points(x, y, sides, radius, rotation);
+                for (i, start) in points.iter().enumerate() {
+                    self.line(*start, points[(i + 1) % points.len()], false);
+                }
+            }
+            DrawCommand::Triangle(x1, y1, x2, y2, x3, y3) => {
+                self.fill(&[(f(x1), f(y1)), (f(x2), f(y2)), (f(x3), f(y3))]);
+            }
+            DrawCommand::Translate(x, y) => {
+                self.transform = self.transform.then([1., 0., 0., 1., f(x), f(y)]);
+            }
+            DrawCommand::Scale(x, y) => {
+                self.transform = self.transform.then([x, 0., 0., y, 0., 0.]);
+            }
+            DrawCommand::Rotate(angle) => {
+                let (sin, cos) = f(angle).to_radians().sin_cos();
+                self.transform = self.transform.then([cos, sin, -sin, cos, 0., 0.]);
+            }
+            DrawCommand::Reset => self.transform = Transform::IDENTITY,
+        }
+    }
+
+    /// Gets the corners of a regular polygon
+    fn poly_points(x: i32, y: i32, sides: i32, radius: i32, rotation: i32) -> Vec<(f64, f64)> {
+        let sides = sides.clamp(3, 100);
+        (0..sides)
+            .map(|i| {
+                let angle =
+                    (f64::from(rotation) + f64::from(i) * 360. / f64::from(sides)).to_radians();
+                (
+                    f64::from(x) + f64::from(radius) * angle.cos(),
+                    f64::from(y) + f64::from(radius) * angle.sin(),
+                )
+            })
+            .collect()
+    }
+
+    /// Fills a rectangle from one corner and its size, which can be negative
+    fn rect(&mut self, x: f64, y: f64, w: f64, h: f64) {
+        self.fill(&[(x, y), (x + w, y), (x + w, y + h), (x, y + h)]);
+    }
+
+    /// Draws a line as thick as the stroke, optionally extending the ends by half the stroke
+    fn line(&mut self, (x1, y1): (f64, f64), (x2, y2): (f64, f64), cap: bool) {
+        let len = (x2 - x1).hypot(y2 - y1);
+        let half = self.stroke / 2.;
+        let (dx, dy) = if len == 0. {
+            (half, 0.)
+        } else {
+            ((x2 - x1) / len * half, (y2 - y1) / len * half)
+        };
+        let (cx, cy) = if cap { (dx, dy) } else { (0., 0.) };
+
+        self.fill(&[
+            (x1 - cx - dy, y1 - cy + dx),
+            (x1 - cx + dy, y1 - cy - dx),
+            (x2 + cx + dy, y2 + cy - dx),
+            (x2 + cx - dy, y2 + cy + dx),
+        ]);
+    }
+
+    /// Fills a convex polygon (before transforming it) with the current colour
+    fn fill(&mut self, points: &[(f64, f64)]) {
+        let points: Vec<_> = points.iter().map(|x| self.transform.apply(*x)).collect();
+        let size = self.size as f64;
+
+        let bound = |f: fn(f64, f64) -> f64, get: fn(&(f64, f64)) -> f64| {
+            points.iter().map(get).fold(get(&points[0]), f)
+        };
+        let (min_x, max_x) = (bound(f64::min, |p| p.0), bound(f64::max, |p| p.0));
+        let (min_y, max_y) = (bound(f64::min, |p| p.1), bound(f64::max, |p| p.1));
+        let range = |min: f64, max: f64| {
+            let start = (min - 0.5).ceil().clamp(0., size) as usize;
+            let end = ((max - 0.5).floor() + 1.).clamp(0., size) as usize;
+            start..end
+        };
+
+        // Which side of each edge the inside is on, since shapes can be wound either way
+        let area: f64 = (0..points.len())
+            .map(|i| {
+                let (a, b) = (points[i], points[(i + 1) % points.len()]);
+                a.0 * b.1 - b.0 * a.1
+            })
+            .sum();
+        if area == 0. {
+            return;
+        }
+
+        for y in range(min_y, max_y) {
+            for x in range(min_x, max_x) {
+                // Nudged so that pixels exactly on an edge are only drawn by one of two shapes
+                // that share it
+                let centre = (x as f64 + 0.5 - 1e-9, y as f64 + 0.5 - 1e-9);
+                let inside = (0..points.len()).all(|i| {
+                    let (a, b) = (points[i], points[(i + 1) % points.len()]);
+                    let cross = (b.0 - a.0) * (centre.1 - a.1) - (b.1 - a.1) * (centre.0 - a.0);
+                    cross * area.signum() >= 0.
+                });
+                if inside {
+                    self.blend(x, y);
+                }
+            }
+        }
+    }
+
+    /// Blends the current colour onto a pixel
+    fn blend(&mut self, x: usize, y: usize) {
+        let Rgba { r, g, b, a } = self.colour;
+        let pixel = &mut self.pixels[y * self.size + x];
+        let alpha = f64::from(a) / 255.;
+        let mix = |src: u8, dst: u8| {
+            (f64::from(src) * alpha + f64::from(dst) * (1. - alpha)).round() as u8
+        };
+
+        *pixel = Rgba {
+            r: mix(r, pixel.r),
+            g: mix(g, pixel.g),
+            b: mix(b, pixel.b),
+            a: (f64::from(a) + f64::from(pixel.a) * (1. - alpha)).round() as u8,
+        };
+    }
+}
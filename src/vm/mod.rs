@@ -0,0 +1,1212 @@
+//! A reference interpreter for mindustry logic.
+//!
+//! This runs programs made of [`Statement`]s the way a logic processor in the game does, so that
+//! code can be tested without the game. Instructions that look at or change the world around the
+//! processor go through a [`World`], which tests can implement to script buildings and units.
+//! World processors ([`WprocStatement`]s) are run the same way, with [`Recorder`] keeping a log
+//! of everything they change. Anything else that isn't emulated (including
+//! [`Statement::Unknown`]) does nothing.
+//!
+//! [`Statement`]: crate::parser::statements::Statement
+//! [`Statement::Unknown`]: crate::parser::statements::Statement::Unknown
+//! [`WprocStatement`]: crate::parser::statements::WprocStatement
+//! [`World`]: crate::vm::world::World
+//! [`Recorder`]: crate::vm::world::Recorder
+
+/// Blocks that processors can be linked to
+pub mod blocks;
+/// Instructions per tick and the game clock
+pub mod clock;
+/// Stepping through programs with breakpoints and watches
+pub mod debug;
+/// Emulated logic displays
+pub mod display;
+/// Evaluation of `op` instructions
+pub mod ops;
+/// Counting where processors spend their time
+pub mod profile;
+/// Several processors running side by side
+pub mod sim;
+/// Saving and restoring processors
+pub mod snapshot;
+/// Recording and replaying what processors do
+pub mod trace;
+/// Runtime values
+pub mod value;
+/// The world around a processor
+pub mod world;
+/// Running the instructions only world processors have
+mod wproc;
+
+pub use blocks::Block;
+pub use sim::Simulation;
+pub use value::Value;
+pub use world::World;
+
+use crate::parser::{
+    args::{Argument, ConditionOp, Rgba},
+    errs::ParseError,
+    statements::{Statement, WprocStatement},
+};
+use crate::program::Program;
+use blocks::Memory;
+use clock::{MAX_ACCUMULATED_TICKS, MAX_IPT, ProcessorKind, TICKS_PER_SECOND};
+use display::{Display, DrawCommand};
+use ops::OpParts;
+use profile::Profile;
+use std::collections::HashMap;
+use std::marker::PhantomData;
+use trace::{Effect, Trace};
+use world::{ControlCommand, LocateQuery, NoWorld, RadarQuery, UnitCommand};
+
+/// Globals that come from the world around the processor instead of naming content. Every other
+/// global (like `@copper` or `@health`) is a content constant.
+const WORLD_GLOBALS: &[&str] = &[
+    "this",
+    "thisx",
+    "thisy",
+    "unit",
+    "waveNumber",
+    "waveTime",
+    "mapw",
+    "maph",
+    "server",
+    "client",
+    "clientLocale",
+    "clientUnit",
+    "clientName",
+    "clientTeam",
+    "clientMobile",
+];
+
+/// The most text a processor can print before flushing it
+const MAX_TEXT_BUFFER: usize = 400;
+
+/// The most drawing commands a processor can queue before flushing them
+const MAX_GRAPHICS_BUFFER: usize = 256;
+
+/// What happened after running a single instruction
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Step {
+    /// The program can keep running
+    Continue,
+    /// The program reached its end (by running the last instruction or an `end` instruction), and
+    /// will start again from the top
+    End,
+    /// A `stop` instruction was run, so the processor won't go any further
+    Stopped,
+    /// A `wait` instruction is waiting for the clock, so the processor won't go any further until
+    /// a later tick
+    Waiting,
+}
+
+/// A logic processor running a program.
+///
+/// Variables start out as `null`, and are created when they're first written to. Writes to
+/// constants (like `true` or a number) and to read-only globals are ignored, but writing to
+/// `@counter` jumps like it does in the game.
+///
+/// Blocks are linked by name, like `cell1`. Each link is a constant holding the building, so
+/// instructions can use it directly or through another variable.
+///
+/// Everything else the processor can see comes from its [`World`], which is empty by default and
+/// can be replaced with [`Processor::with_world`].
+///
+/// Instructions can be run one at a time with [`Processor::step`] and [`Processor::run`], or a
+/// tick at a time with [`Processor::advance`]. Only the latter moves the clock that `wait`,
+/// `@tick` and `@time` use, running as many instructions per tick as the processor would in the
+/// game.
+///
+/// # Examples
+///
+/// ```
+/// # use mlog_parse::vm::{Processor, Value, blocks::Memory};
+/// const SRC: &str = r#"
+///     set i 0
+///     loop:
+///         op add i i 1
+///         write i cell1 i
+///     jump loop lessThan i 5
+///     end
+/// "#;
+///
+/// let mut processor = Processor::parse(SRC).unwrap();
+/// processor.link("cell1", Memory::cell());
+/// processor.run(100);
+///
+/// assert_eq!(processor.get("i"), Value::Number(5.));
+/// assert_eq!(processor.memory("cell1").unwrap().values()[..6], [0., 1., 2., 3., 4., 5.]);
+/// ```
+#[derive(Debug, Clone)]
+pub struct Processor<'a, W = NoWorld, S = Statement<'a>> {
+    statements: Vec<S>,
+    counter: usize,
+    variables: HashMap<String, Value>,
+    links: Vec<(String, Option<Block>)>,
+    text_buffer: String,
+    graphics_buffer: Vec<DrawCommand>,
+    rng: u64,
+    world: W,
+    unit: Value,
+    ipt: f64,
+    accumulator: f64,
+    tick: u64,
+    waits: HashMap<usize, f64>,
+    trace: Option<Trace>,
+    profile: Option<Profile>,
+    source: PhantomData<&'a str>,
+}
+
+impl<'a> Processor<'a> {
+    /// Creates a processor running the given statements, starting at the first one
+    #[must_use]
+    pub fn new(statements: Vec<Statement<'a>>) -> Self {
+        Self::with_statements(statements)
+    }
+
+    /// Parses a program and creates a processor running it.
+    ///
+    /// # Errors
+    ///
+    /// Returns every error found in the source if there were any.
+    pub fn parse(src: &'a str) -> Result<Self, Vec<ParseError<'a>>> {
+        Program::parse(src).map(Self::from)
+    }
+}
+
+impl<'a> Processor<'a, NoWorld, WprocStatement<'a>> {
+    /// Creates a world processor running the given statements, starting at the first one
+    #[must_use]
+    pub fn new_wproc(statements: Vec<WprocStatement<'a>>) -> Self {
+        Self::with_statements(statements)
+    }
+
+    /// Parses a world processor program and creates a processor running it.
+    ///
+    /// # Errors
+    ///
+    /// Returns every error found in the source if there were any.
+    pub fn parse_wproc(src: &'a str) -> Result<Self, Vec<ParseError<'a>>> {
+        Program::parse(src).map(Self::from)
+    }
+}
+
+impl<'a, S: Instruction<'a>> Processor<'a, NoWorld, S> {
+    fn with_statements(statements: Vec<S>) -> Self {
+        Self {
+            statements,
+            counter: 0,
+            variables: HashMap::new(),
+            links: Vec::new(),
+            text_buffer: String::new(),
+            graphics_buffer: Vec::new(),
+            rng: 0x2545_f491_4f6c_dd1d,
+            world: NoWorld,
+            unit: Value::Null,
+            ipt: ProcessorKind::default().ipt(),
+            accumulator: 0.,
+            tick: 0,
+            waits: HashMap::new(),
+            trace: None,
+            profile: None,
+            source: PhantomData,
+        }
+    }
+}
+
+impl<'a, W: World, S: Instruction<'a>> Processor<'a, W, S> {
+    /// Replaces the world around the processor, keeping everything else
+    #[must_use]
+    pub fn with_world<V: World>(self, world: V) -> Processor<'a, V, S> {
+        Processor {
+            statements: self.statements,
+            counter: self.counter,
+            variables: self.variables,
+            links: self.links,
+            text_buffer: self.text_buffer,
+            graphics_buffer: self.graphics_buffer,
+            rng: self.rng,
+            world,
+            unit: self.unit,
+            ipt: self.ipt,
+            accumulator: self.accumulator,
+            tick: self.tick,
+            waits: self.waits,
+            trace: self.trace,
+            profile: self.profile,
+            source: PhantomData,
+        }
+    }
+
+    /// Gets the world around the processor
+    #[must_use]
+    pub fn world(&self) -> &W {
+        &self.world
+    }
+
+    /// Gets the world around the processor mutably, e.g. to move things around between steps
+    pub fn world_mut(&mut self) -> &mut W {
+        &mut self.world
+    }
+
+    /// Sets the seed used for `op rand`, so that runs can be repeated
+    #[must_use]
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        // xorshift gets stuck on 0
+        self.rng = seed.max(1);
+        self
+    }
+
+    /// Sets the number of instructions run every tick to that of a kind of processor
+    #[must_use]
+    pub fn with_kind(mut self, kind: ProcessorKind) -> Self {
+        self.ipt = kind.ipt();
+        self
+    }
+
+    /// Gets the number of instructions run every tick
+    #[must_use]
+    pub fn ipt(&self) -> f64 {
+        self.ipt
+    }
+
+    /// Sets the number of instructions run every tick, up to [`MAX_IPT`]. This can be a fraction,
+    /// in which case the leftover is saved up for later ticks.
+    pub fn set_ipt(&mut self, ipt: f64) {
+        self.ipt = ipt.clamp(0., MAX_IPT);
+    }
+
+    /// Gets the number of ticks that have passed, which is what `@tick` reads
+    #[must_use]
+    pub fn tick(&self) -> u64 {
+        self.tick
+    }
+
+    /// Sets the number of ticks that have passed, without running anything
+    pub fn set_tick(&mut self, tick: u64) {
+        self.tick = tick;
+    }
+
+    /// Gets the statements being run
+    #[must_use]
+    pub fn statements(&self) -> &[S] {
+        &self.statements
+    }
+
+    /// Gets the index of the next instruction to run. This can be past the end of the program, in
+    /// which case it wraps around to 0 when the next instruction is run.
+    #[must_use]
+    pub fn counter(&self) -> usize {
+        self.counter
+    }
+
+    /// Sets the index of the next instruction to run
+    pub fn set_counter(&mut self, counter: usize) {
+        self.counter = counter;
+    }
+
+    /// Gets the value of a variable, constant or global (e.g. `x`, `true` or `@counter`), the same
+    /// way an instruction reading it would.
+    #[must_use]
+    pub fn get(&self, name: &str) -> Value {
+        self.read(&Argument::from(name))
+    }
+
+    /// Sets a variable. This follows the same rules as an instruction writing to it, so constants
+    /// can't be changed and setting `@counter` jumps.
+    pub fn set(&mut self, name: &str, value: Value) {
+        self.write(name, value);
+    }
+
+    /// Gets every variable that has been set, along with its value
+    pub fn variables(&self) -> impl Iterator<Item = (&str, &Value)> {
+        self.variables.iter().map(|(k, v)| (k.as_str(), v))
+    }
+
+    /// Links a block with the given name, replacing any block already linked with that name
+    pub fn link(&mut self, name: impl Into<String>, block: impl Into<Block>) {
+        *self.link_slot(&name.into()) = Some(block.into());
+    }
+
+    /// Gets the block linked with a name, adding the name without a block if it isn't linked.
+    /// A name without a block is still a link, so it keeps its place for `getlink` and can't be
+    /// written to, but instructions using it act like it isn't linked to anything.
+    pub(super) fn link_slot(&mut self, name: &str) -> &mut Option<Block> {
+        let index = match self.links.iter().position(|x| x.0 == name) {
+            Some(index) => index,
+            None => {
+                self.links.push((name.to_string(), None));
+                self.links.len() - 1
+            }
+        };
+        &mut self.links[index].1
+    }
+
+    /// Checks whether a name is linked, even if it has no block yet
+    fn is_linked(&self, name: &str) -> bool {
+        self.links.iter().any(|x| x.0 == name)
+    }
+
+    /// Gets a linked block
+    #[must_use]
+    pub fn block(&self, name: &str) -> Option<&Block> {
+        self.links
+            .iter()
+            .find(|x| x.0 == name)
+            .and_then(|x| x.1.as_ref())
+    }
+
+    /// Gets a mutable reference to a linked block
+    pub fn block_mut(&mut self, name: &str) -> Option<&mut Block> {
+        self.links
+            .iter_mut()
+            .find(|x| x.0 == name)
+            .and_then(|x| x.1.as_mut())
+    }
+
+    /// Gets the memory of a linked memory cell or bank
+    #[must_use]
+    pub fn memory(&self, name: &str) -> Option<&Memory> {
+        self.block(name).and_then(Block::as_memory)
+    }
+
+    /// Gets the memory of a linked memory cell or bank mutably, e.g. to fill it before running
+    pub fn memory_mut(&mut self, name: &str) -> Option<&mut Memory> {
+        self.block_mut(name).and_then(Block::as_memory_mut)
+    }
+
+    /// Gets the text of a linked message block
+    #[must_use]
+    pub fn message(&self, name: &str) -> Option<&str> {
+        self.block(name).and_then(Block::as_message)
+    }
+
+    /// Gets a linked logic display
+    #[must_use]
+    pub fn display(&self, name: &str) -> Option<&Display> {
+        self.block(name).and_then(Block::as_display)
+    }
+
+    /// Gets the drawing commands that haven't been flushed yet
+    #[must_use]
+    pub fn graphics_buffer(&self) -> &[DrawCommand] {
+        &self.graphics_buffer
+    }
+
+    /// Gets the text that has been printed but not flushed yet
+    #[must_use]
+    pub fn text_buffer(&self) -> &str {
+        &self.text_buffer
+    }
+
+    /// Starts recording a [`Trace`] of every instruction that runs, throwing away the one being
+    /// recorded if there is one
+    pub fn start_trace(&mut self) {
+        self.trace = Some(Trace::default());
+    }
+
+    /// Gets the trace being recorded
+    #[must_use]
+    pub fn trace(&self) -> Option<&Trace> {
+        self.trace.as_ref()
+    }
+
+    /// Stops recording a trace, returning it
+    pub fn take_trace(&mut self) -> Option<Trace> {
+        self.trace.take()
+    }
+
+    /// Starts counting how often every instruction runs, throwing away the counts so far if
+    /// there are any
+    pub fn start_profile(&mut self) {
+        self.profile = Some(Profile::new(self.statements.len()));
+    }
+
+    /// Gets the profile being counted
+    #[must_use]
+    pub fn profile(&self) -> Option<&Profile> {
+        self.profile.as_ref()
+    }
+
+    /// Stops counting, returning the profile
+    pub fn take_profile(&mut self) -> Option<Profile> {
+        self.profile.take()
+    }
+
+    /// Runs up to `max_steps` instructions, stopping early if the program ends or is stopped.
+    /// Returns what happened on the last instruction that was run, which is [`Step::Continue`] if
+    /// the limit was reached (or `max_steps` is 0).
+    pub fn run(&mut self, max_steps: usize) -> Step {
+        for _ in 0..max_steps {
+            match self.step() {
+                Step::Continue => {}
+                x => return x,
+            }
+        }
+        Step::Continue
+    }
+
+    /// Runs the processor for `ticks` ticks of game time
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use mlog_parse::vm::{Processor, Value, clock::ProcessorKind};
+    /// const SRC: &str = r#"
+    ///     op add blinks blinks 1
+    ///     wait 0.5
+    /// "#;
+    ///
+    /// let mut processor = Processor::parse(SRC).unwrap().with_kind(ProcessorKind::Micro);
+    /// processor.advance(60);
+    ///
+    /// assert_eq!(processor.get("blinks"), Value::Number(2.));
+    /// assert_eq!(processor.get("@second"), Value::Number(1.));
+    /// ```
+    pub fn advance(&mut self, ticks: u64) {
+        for _ in 0..ticks {
+            self.run_tick();
+        }
+    }
+
+    /// Runs the instructions for a single tick and moves the clock forward. Like in the game, the
+    /// rest of the tick is skipped after a `wait` or `stop`, and the instructions that weren't run
+    /// are saved up (to a limit) for later ticks.
+    pub fn run_tick(&mut self) {
+        self.start_tick();
+        while self.has_saved_instruction() {
+            if matches!(self.step_saved(), Step::Stopped | Step::Waiting) {
+                break;
+            }
+        }
+        self.tick += 1;
+    }
+
+    /// Saves up the instructions for a new tick
+    pub(super) fn start_tick(&mut self) {
+        self.accumulator = (self.accumulator + self.ipt).min(MAX_ACCUMULATED_TICKS * self.ipt);
+    }
+
+    /// Checks whether there's an instruction saved up to run in this tick
+    pub(super) fn has_saved_instruction(&self) -> bool {
+        self.accumulator >= 1.
+    }
+
+    /// Runs an instruction that was saved up for this tick
+    pub(super) fn step_saved(&mut self) -> Step {
+        self.accumulator -= 1.;
+        self.step()
+    }
+
+    /// Runs a single instruction
+    pub fn step(&mut self) -> Step {
+        if self.statements.is_empty() {
+            return Step::End;
+        }
+        if self.counter >= self.statements.len() {
+            self.counter = 0;
+        }
+
+        let index = self.counter;
+        self.counter += 1;
+        if let Some(trace) = &mut self.trace {
+            trace.start_step(index, self.tick);
+        }
+        if let Some(profile) = &mut self.profile {
+            profile.hit(index, 1. / self.ipt);
+        }
+
+        // Cloned so that the processor can be changed while running it. Statements only borrow
+        // the source, so this is cheap for everything other than unknown instructions.
+        let statement = self.statements[index].clone();
+
+        let step = match statement.split() {
+            Ok(statement) => self.execute(index, statement),
+            Err(statement) => {
+                self.execute_wproc(statement);
+                Step::Continue
+            }
+        };
+        // Stopping and waiting run the same instruction again
+        if step != Step::Continue {
+            self.counter = index;
+        }
+        if let Some(current) = self.trace.as_mut().and_then(Trace::current) {
+            current.counter = self.counter;
+        }
+
+        if step != Step::Continue {
+            step
+        } else if self.counter >= self.statements.len() {
+            Step::End
+        } else {
+            Step::Continue
+        }
+    }
+
+    /// Runs the statement at `index`, if it's one that any processor can run. Returns
+    /// [`Step::Continue`] unless the processor has to stay on this statement.
+    fn execute(&mut self, index: usize, statement: Statement<'a>) -> Step {
+        if let Some(op) = OpParts::from_statement(&statement) {
+            let (a, b) = (self.read(&op.a), self.read(&op.b));
+            let rand = if op.op.is_random() {
+                self.next_rand()
+            } else {
+                0.
+            };
+            self.write(op.result, op.op.eval(&a, &b, rand));
+        } else {
+            match statement {
+                Statement::Set { value, var } => {
+                    let value = self.read(&value);
+                    self.write(var, value);
+                }
+                Statement::Jump {
+                    target,
+                    cond,
+                    lhs,
+                    rhs,
+                } => {
+                    let taken = self.test(cond, lhs, rhs);
+                    if taken {
+                        self.counter = target.index;
+                    }
+                    if let Some(profile) = &mut self.profile {
+                        profile.jump(index, taken);
+                    }
+                }
+                Statement::Select {
+                    result,
+                    cond,
+                    lhs,
+                    rhs,
+                    true_option,
+                    false_option,
+                } => {
+                    let value = if self.test(cond, lhs, rhs) {
+                        self.read(&true_option)
+                    } else {
+                        self.read(&false_option)
+                    };
+                    self.write(result, value);
+                }
+                Statement::Read {
+                    cell,
+                    index,
+                    result,
+                } => {
+                    let index = self.read(&index).num();
+                    if let Some(memory) = self.memory_at(&cell) {
+                        let value = memory.read(index);
+                        self.write(result, Value::number(value));
+                    }
+                }
+                Statement::Write { value, cell, index } => {
+                    let (value, index) = (self.read(&value).num(), self.read(&index).num());
+                    if let Value::Building(cell) = self.read(&cell)
+                        && let Some(memory) = self.memory_mut(&cell)
+                    {
+                        memory.write(index, value);
+                        self.record(Effect::Write { cell, index, value });
+                    }
+                }
+                Statement::Print { text } if self.can_print() => {
+                    let text = self.read(&text).to_string();
+                    self.text_buffer.push_str(&text);
+                }
+                Statement::PrintChar { char } if self.can_print() => {
+                    let value = self.read(&char);
+                    // Content would print its emoji, which isn't known here
+                    if !value.is_obj() {
+                        // Java's (char) cast keeps the lowest 16 bits
+                        let code = value.num().floor() as i32 as u16;
+                        let char =
+                            char::from_u32(code.into()).unwrap_or(char::REPLACEMENT_CHARACTER);
+                        self.text_buffer.push(char);
+                    }
+                }
+                Statement::Format { f_string } if self.can_print() => {
+                    let value = self.read(&f_string).to_string();
+                    self.format(&value);
+                }
+                Statement::PrintFlush { output } => {
+                    let text: String = self.text_buffer.chars().take(MAX_TEXT_BUFFER).collect();
+                    let output = self.read(&output);
+                    if let Value::Building(name) = &output
+                        && let Some(Block::Message(message)) = self.block_mut(name)
+                    {
+                        message.clone_from(&text);
+                    }
+                    self.text_buffer.clear();
+                    self.record(Effect::PrintFlush { output, text });
+                }
+                Statement::DrawFlush { output } => {
+                    let commands = std::mem::take(&mut self.graphics_buffer);
+                    let output = self.read(&output);
+                    if let Value::Building(name) = &output
+                        && let Some(display) = self.block_mut(name).and_then(Block::as_display_mut)
+                    {
+                        display.draw(&commands);
+                    }
+                    self.record(Effect::DrawFlush { output, commands });
+                }
+                Statement::GetLink { index, result } => {
+                    let index = self.read(&index).num() as i32;
+                    let link = usize::try_from(index).ok().and_then(|i| {
+                        self.world.link(i).or_else(|| {
+                            let name = &self.links.get(i)?.0;
+                            Some(Value::Building(name.clone()))
+                        })
+                    });
+                    self.write(result, link.unwrap_or_default());
+                }
+                Statement::Sensor {
+                    block,
+                    property,
+                    result,
+                } => {
+                    let (target, property) = (self.read(&block), self.read(&property));
+                    let value = if let Value::Content(x) = &property
+                        && x == "enabled"
+                        && let Some(enabled) = self.switch_mut(&target)
+                    {
+                        Value::bool(*enabled)
+                    } else {
+                        self.world.sensor(&target, &property)
+                    };
+                    self.write(result, value);
+                }
+                Statement::Radar {
+                    m1,
+                    m2,
+                    m3,
+                    sort,
+                    block,
+                    order,
+                    result,
+                } => {
+                    let from = self.read(&block);
+                    let query = self.radar_query(from, [m1, m2, m3], &sort, &order);
+                    let value = self.world.radar(&query);
+                    self.write(result, value);
+                }
+                Statement::URadar {
+                    m1,
+                    m2,
+                    m3,
+                    sort,
+                    order,
+                    result,
+                    ..
+                } => {
+                    let value = if self.unit == Value::Null {
+                        Value::Null
+                    } else {
+                        let query =
+                            self.radar_query(self.unit.clone(), [m1, m2, m3], &sort, &order);
+                        self.world.radar(&query)
+                    };
+                    self.write(result, value);
+                }
+                Statement::ULocate {
+                    find,
+                    group,
+                    enemy,
+                    ore,
+                    outx,
+                    outy,
+                    found,
+                    building,
+                } => {
+                    let query = LocateQuery {
+                        find: name(&find),
+                        group: name(&group),
+                        enemy: self.read(&enemy).is_truthy(),
+                        ore: self.read(&ore),
+                    };
+                    let located = match self.unit {
+                        Value::Null => None,
+                        ref unit => self.world.unit_locate(unit, &query),
+                    };
+                    self.write(found, Value::bool(located.is_some()));
+                    if let Some(located) = located {
+                        self.write(outx, Value::number(located.x));
+                        self.write(outy, Value::number(located.y));
+                        self.write(building, located.building);
+                    }
+                }
+                Statement::UBind { unit_type } => {
+                    let unit_type = self.read(&unit_type);
+                    self.unit = self.world.unit_bind(&unit_type);
+                    self.record(Effect::UnitBind(self.unit.clone()));
+                }
+                // Units only follow commands while they're bound
+                ref x
+                    if self.unit != Value::Null
+                        && let Some((command, outputs)) = self.unit_command(x) =>
+                {
+                    let mut values = self.world.unit_control(&self.unit, &command).into_iter();
+                    self.record(Effect::UnitControl(command));
+                    for output in outputs {
+                        self.write(output, values.next().unwrap_or_default());
+                    }
+                }
+                ref x if let Some((block, command)) = self.control_command(x) => {
+                    if let ControlCommand::Enabled(enabled) = command
+                        && let Some(switch) = self.switch_mut(&block)
+                    {
+                        *switch = enabled;
+                    } else {
+                        self.world.control(&block, &command);
+                    }
+                    self.record(Effect::Control { block, command });
+                }
+                Statement::End {} => self.counter = self.statements.len(),
+                Statement::Stop {} => return Step::Stopped,
+                Statement::Wait { time } => {
+                    let time = self.read(&time).num();
+                    if !self.wait(index, time) {
+                        return Step::Waiting;
+                    }
+                }
+                ref x if self.graphics_buffer.len() < MAX_GRAPHICS_BUFFER => {
+                    if let Some(command) = self.draw_command(x) {
+                        self.graphics_buffer.push(command);
+                    }
+                }
+                _ => {}
+            }
+        }
+        Step::Continue
+    }
+
+    /// Gets the value of an argument
+    fn read(&self, arg: &Argument) -> Value {
+        match arg {
+            Argument::Number(x) => Value::number(*x),
+            Argument::String(x) => Value::String(x.replace("\\n", "\n")),
+            Argument::Colour(x) => {
+                let bits = u32::from_be_bytes([x.r, x.g, x.b, x.a]);
+                Value::number(f64::from_bits(bits.into()))
+            }
+            Argument::Variable("true") => Value::bool(true),
+            Argument::Variable("false") => Value::bool(false),
+            Argument::Variable("null") => Value::Null,
+            Argument::Variable(x) => match self.variables.get(*x) {
+                Some(value) => value.clone(),
+                None if self.is_linked(x) => Value::Building(x.to_string()),
+                None => Value::Null,
+            },
+            Argument::GlobalVar(x) => match *x {
+                "counter" => Value::number(self.counter as f64),
+                "unit" => self.unit.clone(),
+                "links" => {
+                    let count = self.world.link_count().unwrap_or(self.links.len());
+                    Value::number(count as f64)
+                }
+                "ipt" => Value::number(self.ipt),
+                "tick" => Value::number(self.tick as f64),
+                "second" => Value::number(self.tick as f64 / TICKS_PER_SECOND),
+                "minute" => Value::number(self.tick as f64 / TICKS_PER_SECOND / 60.),
+                // In milliseconds
+                "time" => Value::number(self.tick as f64 / TICKS_PER_SECOND * 1000.),
+                // These are floats in the game
+                "pi" => Value::number(std::f32::consts::PI.into()),
+                "e" => Value::number(std::f32::consts::E.into()),
+                "degToRad" => Value::number((std::f32::consts::PI / 180.).into()),
+                "radToDeg" => Value::number((180. / std::f32::consts::PI).into()),
+                x if WORLD_GLOBALS.contains(&x) => Value::Null,
+                x => Value::Content(x.to_string()),
+            },
+        }
+    }
+
+    /// Writes to a variable, ignoring writes to anything that can't be changed
+    fn write(&mut self, name: &str, value: Value) {
+        match Argument::from(name) {
+            Argument::Variable("true" | "false" | "null") => {}
+            // Negative values saturate to 0, which is where the game would wrap them to anyway
+            Argument::GlobalVar("counter") => self.counter = value.num() as usize,
+            Argument::Variable(x) if !self.is_linked(x) => {
+                if let Some(current) = self.trace.as_mut().and_then(Trace::current) {
+                    current.writes.push((x.to_string(), value.clone()));
+                }
+                self.variables.insert(x.to_string(), value);
+            }
+            _ => {}
+        }
+    }
+
+    /// Turns a `draw` statement into the command it queues, if it draws anything
+    fn draw_command(&self, statement: &Statement) -> Option<DrawCommand> {
+        let int = |arg: &Argument| pack(self.read(arg).num());
+        let channel = |arg: &Argument| self.read(arg).num().clamp(0., 255.) as u8;
+
+        Some(match statement {
+            Statement::DrawClear { r, g, b } => DrawCommand::Clear(Rgba {
+                r: channel(r),
+                g: channel(g),
+                b: channel(b),
+                a: 255,
+            }),
+            Statement::DrawColour { r, g, b, a } => DrawCommand::Colour(Rgba {
+                r: channel(r),
+                g: channel(g),
+                b: channel(b),
+                a: channel(a),
+            }),
+            Statement::DrawCol { packed_colour } => {
+                let bits = self.read(packed_colour).num().to_bits() as u32;
+                let [r, g, b, a] = bits.to_be_bytes();
+                DrawCommand::Colour(Rgba { r, g, b, a })
+            }
+            Statement::DrawStroke { width } => DrawCommand::Stroke(int(width)),
+            Statement::DrawLine { x, y, x2, y2 } => {
+                DrawCommand::Line(int(x), int(y), int(x2), int(y2))
+            }
+            Statement::DrawRect { x, y, w, h } => DrawCommand::Rect(int(x), int(y), int(w), int(h)),
+            Statement::DrawLineRect { x, y, w, h } => {
+                DrawCommand::LineRect(int(x), int(y), int(w), int(h))
+            }
+            Statement::DrawPoly {
+                x,
+                y,
+                w,
+                h,
+                rotation,
+            } => DrawCommand::Poly(int(x), int(y), int(w), int(h), int(rotation)),
+            Statement::DrawLinePoly {
+                x,
+                y,
+                w,
+                h,
+                rotation,
+            } => DrawCommand::LinePoly(int(x), int(y), int(w), int(h), int(rotation)),
+            Statement::DrawTri {
+                x1,
+                y1,
+                x2,
+                y2,
+                x3,
+                y3,
+            } => DrawCommand::Triangle(int(x1), int(y1), int(x2), int(y2), int(x3), int(y3)),
+            Statement::DrawTranslate { x, y } => DrawCommand::Translate(int(x), int(y)),
+            // Scales are stored in steps of 0.05
+            Statement::DrawScale { x, y } => {
+                let step = |arg: &Argument| f64::from(pack(self.read(arg).num() / 0.05)) * 0.05;
+                DrawCommand::Scale(step(x), step(y))
+            }
+            Statement::DrawRotate { angle } => DrawCommand::Rotate(int(angle)),
+            Statement::DrawReset {} => DrawCommand::Reset,
+            _ => return None,
+        })
+    }
+
+    /// Builds the query of a `radar` or `uradar` statement
+    fn radar_query(
+        &self,
+        from: Value,
+        [m1, m2, m3]: [Argument; 3],
+        sort: &Argument,
+        order: &Argument,
+    ) -> RadarQuery {
+        RadarQuery {
+            from,
+            targets: [name(&m1), name(&m2), name(&m3)],
+            sort: name(sort),
+            descending: self.read(order).is_truthy(),
+        }
+    }
+
+    /// Turns a `ucontrol` statement into its command, along with the variables it outputs to
+    fn unit_command<'s>(&self, statement: &Statement<'s>) -> Option<(UnitCommand, Vec<&'s str>)> {
+        let num = |arg: &Argument| self.read(arg).num();
+        let truthy = |arg: &Argument| self.read(arg).is_truthy();
+
+        let command = match statement {
+            Statement::UCIdle {} => UnitCommand::Idle,
+            Statement::UCStop {} => UnitCommand::Stop,
+            Statement::UCUnbind {} => UnitCommand::Unbind,
+            Statement::UCFlag { flag } => UnitCommand::Flag(num(flag)),
+            Statement::UCGetBlock {
+                x,
+                y,
+                building_type,
+                building,
+                floor_type,
+            } => {
+                let command = UnitCommand::GetBlock {
+                    x: num(x),
+                    y: num(y),
+                };
+                return Some((command, vec![building_type, building, floor_type]));
+            }
+            Statement::UCBuild {
+                x,
+                y,
+                block,
+                rotation,
+                config,
+            } => UnitCommand::Build {
+                x: num(x),
+                y: num(y),
+                block: self.read(block),
+                rotation: num(rotation),
+                config: self.read(config),
+            },
+            Statement::UCDeconstruct { x, y } => UnitCommand::Deconstruct {
+                x: num(x),
+                y: num(y),
+            },
+            Statement::UCMove { x, y } => UnitCommand::Move {
+                x: num(x),
+                y: num(y),
+            },
+            Statement::UCPathfind { x, y } => UnitCommand::Pathfind {
+                x: num(x),
+                y: num(y),
+            },
+            Statement::UCAutoPathfind {} => UnitCommand::AutoPathfind,
+            Statement::UCApproach { x, y, radius } => UnitCommand::Approach {
+                x: num(x),
+                y: num(y),
+                radius: num(radius),
+            },
+            Statement::UCWithin {
+                x,
+                y,
+                radius,
+                result,
+            } => {
+                let command = UnitCommand::Within {
+                    x: num(x),
+                    y: num(y),
+                    radius: num(radius),
+                };
+                return Some((command, vec![result]));
+            }
+            Statement::UCBoost { boost } => UnitCommand::Boost(truthy(boost)),
+            Statement::UCMine { x, y } => UnitCommand::Mine {
+                x: num(x),
+                y: num(y),
+            },
+            Statement::UCTarget { x, y, shoot } => UnitCommand::Target {
+                x: num(x),
+                y: num(y),
+                shoot: truthy(shoot),
+            },
+            Statement::UCTargetP { unit, shoot } => UnitCommand::TargetP {
+                unit: self.read(unit),
+                shoot: truthy(shoot),
+            },
+            Statement::UCItemTake { from, item, amount } => UnitCommand::ItemTake {
+                from: self.read(from),
+                item: self.read(item),
+                amount: num(amount),
+            },
+            Statement::UCItemDrop { to, amount } => UnitCommand::ItemDrop {
+                to: self.read(to),
+                amount: num(amount),
+            },
+            Statement::UCPayloadTake { units } => UnitCommand::PayTake {
+                units: truthy(units),
+            },
+            Statement::UCPayloadDrop {} => UnitCommand::PayDrop,
+            Statement::UCPayloadEnter {} => UnitCommand::PayEnter,
+            _ => return None,
+        };
+        Some((command, Vec::new()))
+    }
+
+    /// Gets the linked switch a value points to, if it's one
+    fn switch_mut(&mut self, block: &Value) -> Option<&mut bool> {
+        match block {
+            Value::Building(name) => self.block_mut(name).and_then(Block::as_switch_mut),
+            _ => None,
+        }
+    }
+
+    /// Turns a `control` statement into the building it controls and its command
+    fn control_command(&self, statement: &Statement) -> Option<(Value, ControlCommand)> {
+        let (block, command) = match statement {
+            Statement::ControlEnabled { block, enabled } => (
+                block,
+                ControlCommand::Enabled(self.read(enabled).is_truthy()),
+            ),
+            Statement::ControlConfig { block, config } => {
+                (block, ControlCommand::Config(self.read(config)))
+            }
+            Statement::ControlColour { block, colour } => {
+                (block, ControlCommand::Colour(self.read(colour).num()))
+            }
+            Statement::ControlShoot { block, x, y, shoot } => (
+                block,
+                ControlCommand::Shoot {
+                    x: self.read(x).num(),
+                    y: self.read(y).num(),
+                    shoot: self.read(shoot).is_truthy(),
+                },
+            ),
+            Statement::ControlShootP { block, unit, shoot } => (
+                block,
+                ControlCommand::ShootP {
+                    unit: self.read(unit),
+                    shoot: self.read(shoot).is_truthy(),
+                },
+            ),
+            _ => return None,
+        };
+        Some((self.read(block), command))
+    }
+
+    /// Checks if the text buffer has room for more text. Like in the game, the last print can go
+    /// over the limit.
+    fn can_print(&self) -> bool {
+        self.text_buffer.chars().count() < MAX_TEXT_BUFFER
+    }
+
+    /// Replaces the lowest numbered `{0}`-`{9}` placeholder in the text buffer with `value`
+    fn format(&mut self, value: &str) {
+        let placeholder = (0..self.text_buffer.len())
+            .filter_map(|i| {
+                let digit = match self.text_buffer.as_bytes().get(i..i + 3)? {
+                    [b'{', digit @ b'0'..=b'9', b'}'] => *digit,
+                    _ => return None,
+                };
+                Some((digit, i))
+            })
+            .min();
+
+        if let Some((_, i)) = placeholder {
+            self.text_buffer.replace_range(i..i + 3, value);
+        }
+    }
+
+    /// Gets the memory of the building an argument holds
+    fn memory_at(&self, arg: &Argument) -> Option<&Memory> {
+        match self.read(arg) {
+            Value::Building(name) => self.memory(&name),
+            _ => None,
+        }
+    }
+
+    /// Adds a side effect to the step being traced, if there is one
+    fn record(&mut self, effect: Effect) {
+        if let Some(current) = self.trace.as_mut().and_then(Trace::current) {
+            current.effects.push(effect);
+        }
+    }
+
+    /// Checks if the `wait` at `index` is done, starting it if it's new. Waits are timed by the
+    /// clock, so they never finish if it isn't moved forward.
+    fn wait(&mut self, index: usize, seconds: f64) -> bool {
+        let now = self.tick as f64;
+        let end = *self
+            .waits
+            .entry(index)
+            .or_insert(now + seconds * TICKS_PER_SECOND);
+
+        let done = now >= end;
+        if done {
+            self.waits.remove(&index);
+        }
+        done
+    }
+
+    /// Checks the condition of a `jump` or `select`
+    fn test(&self, cond: ConditionOp, lhs: Option<Argument>, rhs: Option<Argument>) -> bool {
+        let null = Argument::Variable("null");
+        let a = self.read(&lhs.unwrap_or(null));
+        let b = self.read(&rhs.unwrap_or(null));
+
+        cond.eval(&a, &b)
+    }
+
+    /// Gets a random number between 0 and 1, using xorshift64*
+    fn next_rand(&mut self) -> f64 {
+        self.rng ^= self.rng >> 12;
+        self.rng ^= self.rng << 25;
+        self.rng ^= self.rng >> 27;
+        let x = self.rng.wrapping_mul(0x2545_f491_4f6c_dd1d);
+        (x >> 11) as f64 / (1u64 << 53) as f64
+    }
+}
+
+/// Packs a number for a drawing command, which the game does in 10 bits with a sign bit
+fn pack(x: f64) -> i32 {
+    let x = x as i32;
+    // `unsigned_abs` so that numbers saturated to `i32::MIN` don't overflow
+    x.signum() * (x.unsigned_abs() & 0b1_1111_1111) as i32
+}
+
+/// Gets the name an argument is written as, for operands that are keywords instead of values (like
+/// the `enemy` in `radar enemy any any distance turret1 1 result`)
+fn name(arg: &Argument) -> String {
+    match arg {
+        Argument::Variable(x) | Argument::GlobalVar(x) => (*x).to_string(),
+        x => x.to_string(),
+    }
+}
+
+impl<'a> From<Program<'a, Statement<'a>>> for Processor<'a> {
+    fn from(program: Program<'a, Statement<'a>>) -> Self {
+        Self::new(program.into_parts().0)
+    }
+}
+
+impl<'a> From<Program<'a, WprocStatement<'a>>> for Processor<'a, NoWorld, WprocStatement<'a>> {
+    fn from(program: Program<'a, WprocStatement<'a>>) -> Self {
+        Self::new_wproc(program.into_parts().0)
+    }
+}
+
+impl<'a, W> From<Processor<'a, W>> for Processor<'a, W, WprocStatement<'a>> {
+    /// Turns a processor into one that can run world processor statements, which runs the same
+    /// way since every [`Statement`] is also a [`WprocStatement`]
+    fn from(processor: Processor<'a, W>) -> Self {
+        Processor {
+            statements: processor.statements.into_iter().map(Into::into).collect(),
+            counter: processor.counter,
+            variables: processor.variables,
+            links: processor.links,
+            text_buffer: processor.text_buffer,
+            graphics_buffer: processor.graphics_buffer,
+            rng: processor.rng,
+            world: processor.world,
+            unit: processor.unit,
+            ipt: processor.ipt,
+            accumulator: processor.accumulator,
+            tick: processor.tick,
+            waits: processor.waits,
+            trace: processor.trace,
+            profile: processor.profile,
+            source: PhantomData,
+        }
+    }
+}
+
+/// A statement that a [`Processor`] can run. This is either a [`Statement`], which every
+/// processor can run, or a [`WprocStatement`], which only world processors can.
+pub trait Instruction<'a>: Clone {
+    /// Turns this into a [`Statement`], or gives back the world processor statement if only world
+    /// processors can run it
+    ///
+    /// # Errors
+    ///
+    /// Returns the statement if it only works in world processors.
+    // Boxing the error would slow down every step for no gain, since both are statements
+    #[allow(clippy::result_large_err)]
+    fn split(self) -> Result<Statement<'a>, WprocStatement<'a>>;
+}
+
+impl<'a> Instruction<'a> for Statement<'a> {
+    fn split(self) -> Result<Statement<'a>, WprocStatement<'a>> {
+        Ok(self)
+    }
+}
+
+impl<'a> Instruction<'a> for WprocStatement<'a> {
+    fn split(self) -> Result<Statement<'a>, WprocStatement<'a>> {
+        Statement::try_from(self)
+    }
+}
@@ -0,0 +1,478 @@
+use crate::parser::{
+    args::{Argument, ConditionOp},
+    statements::Statement,
+};
+use crate::vm::value::Value;
+
+/// An operation from an `op` statement. [reference](https://github.com/Anuken/Mindustry/blob/master/core/src/mindustry/logic/LogicOp.java).
+///
+/// These are evaluated exactly like the game does, including its quirks: bitwise operations work
+/// on 64-bit integers, angles are in degrees and some operations are done with 32-bit floats.
+///
+/// # Examples
+///
+/// ```
+/// # use mlog_parse::vm::{ops::LogicOp, Value};
+/// assert_eq!(LogicOp::IntDiv.eval(&(-7.).into(), &2.0.into(), 0.), Value::Number(-4.));
+/// assert_eq!(LogicOp::Div.eval(&1.0.into(), &0.0.into(), 0.), Value::Null);
+/// assert_eq!(LogicOp::Equal.eval(&"a".into(), &1.0.into(), 0.), Value::Number(1.));
+/// ```
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum LogicOp {
+    /// Addition (`add`)
+    Add,
+    /// Subtraction (`sub`)
+    Sub,
+    /// Multiplication (`mul`)
+    Mul,
+    /// Division (`div`)
+    Div,
+    /// Division rounded down (`idiv`)
+    IntDiv,
+    /// Remainder, with the sign of the dividend (`mod`)
+    Mod,
+    /// Remainder, with the sign of the divisor (`emod`)
+    TrueMod,
+    /// Exponentiation (`pow`)
+    Pow,
+    /// Equality, allowing for a small error (`equal`)
+    Equal,
+    /// Inequality (`notEqual`)
+    NotEqual,
+    /// Equality with no type coercion (`strictEqual`)
+    StrictEqual,
+    /// Inequality with no type coercion (`strictNotEqual`)
+    StrictNotEqual,
+    /// Logical and (`land`)
+    LAnd,
+    /// Less than (`lessThan`)
+    LessThan,
+    /// Less than or equal to (`lessThanEq`)
+    LessThanEq,
+    /// Greater than (`greaterThan`)
+    GreaterThan,
+    /// Greater than or equal to (`greaterThanEq`)
+    GreaterThanEq,
+    /// Left shift (`shl`)
+    Shl,
+    /// Arithmetic right shift (`shr`)
+    Shr,
+    /// Logical right shift (`ushr`)
+    UShr,
+    /// Bitwise or (`or`)
+    Or,
+    /// Bitwise and (`b-and`)
+    BAnd,
+    /// Bitwise xor (`xor`)
+    Xor,
+    /// Bitwise not (`flip`)
+    Flip,
+    /// Maximum (`max`)
+    Max,
+    /// Minimum (`min`)
+    Min,
+    /// The angle of a vector in degrees, from 0 to 360 (`angle`)
+    Angle,
+    /// The distance between two angles in degrees (`angleDiff`)
+    AngleDiff,
+    /// The length of a vector (`len`)
+    Len,
+    /// 2D simplex noise (`noise`)
+    Noise,
+    /// Absolute value (`abs`)
+    Abs,
+    /// Sign (`sign`)
+    Sign,
+    /// Natural logarithm (`log`)
+    Log,
+    /// Logarithm with a given base (`logn`)
+    LogN,
+    /// Base 10 logarithm (`log10`)
+    Log10,
+    /// Round down (`floor`)
+    Floor,
+    /// Round up (`ceil`)
+    Ceil,
+    /// Round to the nearest integer, with halves rounded up (`round`)
+    Round,
+    /// Square root (`sqrt`)
+    Sqrt,
+    /// A random number between 0 and the operand (`rand`)
+    Rand,
+    /// Sine of an angle in degrees (`sin`)
+    Sin,
+    /// Cosine of an angle in degrees (`cos`)
+    Cos,
+    /// Tangent of an angle in degrees (`tan`)
+    Tan,
+    /// Inverse sine in degrees (`asin`)
+    ASin,
+    /// Inverse cosine in degrees (`acos`)
+    ACos,
+    /// Inverse tangent in degrees (`atan`)
+    ATan,
+}
+
+/// The parts of an `op` statement: the operation, its operands and where to store the result.
+/// Unary operations have `b` set to `null`.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct OpParts<'a> {
+    /// The operation
+    pub op: LogicOp,
+    /// The first operand
+    pub a: Argument<'a>,
+    /// The second operand
+    pub b: Argument<'a>,
+    /// The variable to store the result in
+    pub result: &'a str,
+}
+
+const NULL: Argument = Argument::Variable("null");
+
+impl<'a> OpParts<'a> {
+    /// Gets the parts of a statement, if it's an `op` statement
+    #[must_use]
+    pub fn from_statement(statement: &Statement<'a>) -> Option<Self> {
+        use LogicOp as Op;
+        use Statement as S;
+
+        let binary = |op, a: &Argument<'a>, b: &Argument<'a>, result: &&'a str| OpParts {
+            op,
+            a: *a,
+            b: *b,
+            result,
+        };
+        let unary = |op, a: &Argument<'a>, result: &&'a str| OpParts {
+            op,
+            a: *a,
+            b: NULL,
+            result,
+        };
+
+        Some(match statement {
+            S::OpAdd { a, b, c } => binary(Op::Add, a, b, c),
+            S::OpSub { a, b, c } => binary(Op::Sub, a, b, c),
+            S::OpMul { a, b, c } => binary(Op::Mul, a, b, c),
+            S::OpDiv { a, b, c } => binary(Op::Div, a, b, c),
+            S::OpExp { a, b, c } => binary(Op::Pow, a, b, c),
+            S::OpIntDiv { a, b, c } => binary(Op::IntDiv, a, b, c),
+            S::OpMod { a, b, c } => binary(Op::Mod, a, b, c),
+            S::OpTrueMod { a, b, c } => binary(Op::TrueMod, a, b, c),
+            S::OpEq { a, b, result } => binary(Op::Equal, a, b, result),
+            S::OpStrictEq { a, b, result } => binary(Op::StrictEqual, a, b, result),
+            S::OpNotEqual { a, b, result } => binary(Op::NotEqual, a, b, result),
+            S::OpStrictNotEqual { a, b, result } => binary(Op::StrictNotEqual, a, b, result),
+            S::OpLAnd { a, b, result } => binary(Op::LAnd, a, b, result),
+            S::OpGreaterThan { a, b, result } => binary(Op::GreaterThan, a, b, result),
+            S::OpLessThan { a, b, result } => binary(Op::LessThan, a, b, result),
+            S::OpGreaterThanEq { a, b, result } => binary(Op::GreaterThanEq, a, b, result),
+            S::OpLessThanEq { a, b, result } => binary(Op::LessThanEq, a, b, result),
+            S::OpBAnd { a, b, result } => binary(Op::BAnd, a, b, result),
+            S::OpOr { a, b, result } => binary(Op::Or, a, b, result),
+            S::OpXor { a, b, result } => binary(Op::Xor, a, b, result),
+            S::OpNot { a, result, .. } => unary(Op::Flip, a, result),
+            S::OpLShift { a, b, result } => binary(Op::Shl, a, b, result),
+            S::OpRShift { a, b, result } => binary(Op::Shr, a, b, result),
+            S::OpURShift { a, b, result } => binary(Op::UShr, a, b, result),
+            S::OpMin { a, b, result } => binary(Op::Min, a, b, result),
+            S::OpMax { a, b, result } => binary(Op::Max, a, b, result),
+            S::OpAngle { x, y, result } => binary(Op::Angle, x, y, result),
+            S::OpAngleDiff { a, b, result } => binary(Op::AngleDiff, a, b, result),
+            S::OpLen { a, b, result } => binary(Op::Len, a, b, result),
+            S::OpRand { d, result } => unary(Op::Rand, d, result),
+            S::OpNoise { x, y, result } => binary(Op::Noise, x, y, result),
+            S::OpAbs { x, result } => unary(Op::Abs, x, result),
+            S::OpSign { x, result } => unary(Op::Sign, x, result),
+            S::OpFloor { a, result, .. } => unary(Op::Floor, a, result),
+            S::OpCeil { x, result } => unary(Op::Ceil, x, result),
+            S::OpRound { x, result } => unary(Op::Round, x, result),
+            S::OpSqrt { x, result } => unary(Op::Sqrt, x, result),
+            S::OpLog { x, result } => unary(Op::Log, x, result),
+            S::OpLogN { a, b, result } => binary(Op::LogN, a, b, result),
+            S::OpLog10 { x, result } => unary(Op::Log10, x, result),
+            S::OpSin { x, result } => unary(Op::Sin, x, result),
+            S::OpCos { x, result } => unary(Op::Cos, x, result),
+            S::OpTan { x, result } => unary(Op::Tan, x, result),
+            S::OpASin { x, result } => unary(Op::ASin, x, result),
+            S::OpACos { x, result } => unary(Op::ACos, x, result),
+            S::OpATan { x, result } => unary(Op::ATan, x, result),
+            _ => return None,
+        })
+    }
+}
+
+impl LogicOp {
+    /// Checks if the operation only uses its first operand
+    #[must_use]
+    pub fn is_unary(self) -> bool {
+        use LogicOp as Op;
+
+        matches!(
+            self,
+            Op::Flip
+                | Op::Abs
+                | Op::Sign
+                | Op::Log
+                | Op::Log10
+                | Op::Floor
+                | Op::Ceil
+                | Op::Round
+                | Op::Sqrt
+                | Op::Rand
+                | Op::Sin
+                | Op::Cos
+                | Op::Tan
+                | Op::ASin
+                | Op::ACos
+                | Op::ATan
+        )
+    }
+
+    /// Checks if the result of the operation is random, so it can't be worked out ahead of time
+    #[must_use]
+    pub fn is_random(self) -> bool {
+        self == Self::Rand
+    }
+
+    /// Evaluates the operation on two values, like the game's `op` instruction.
+    ///
+    /// Operands are converted to numbers with [`Value::num`], except for comparisons between
+    /// two objects (which compare the objects themselves). Results that are NaN or infinite become
+    /// `null`. `random` is used by [`LogicOp::Rand`], and should be between 0 and 1.
+    #[must_use]
+    pub fn eval(self, a: &Value, b: &Value, random: f64) -> Value {
+        match self {
+            Self::StrictEqual => Value::bool(a.strict_eq(b)),
+            Self::StrictNotEqual => Value::bool(!a.strict_eq(b)),
+            Self::Equal if a.is_obj() && b.is_obj() => Value::bool(a.loose_eq(b)),
+            Self::NotEqual if a.is_obj() && b.is_obj() => Value::bool(!a.loose_eq(b)),
+            _ => Value::number(self.eval_num(a.num(), b.num(), random)),
+        }
+    }
+
+    /// Evaluates the operation on two numbers. The result can be NaN or infinite, which the game
+    /// would turn into `null` when storing it. Unary operations ignore `b`.
+    #[must_use]
+    pub fn eval_num(self, a: f64, b: f64, random: f64) -> f64 {
+        // Java's (long) casts saturate and turn NaN into 0, just like `as`
+        let long = |f: fn(i64, i64) -> i64| f(a as i64, b as i64) as f64;
+        let bool = |x: bool| if x { 1. } else { 0. };
+
+        match self {
+            Self::Add => a + b,
+            Self::Sub => a - b,
+            Self::Mul => a * b,
+            Self::Div => a / b,
+            Self::IntDiv => (a / b).floor(),
+            Self::Mod => a % b,
+            Self::TrueMod => ((a % b) + b) % b,
+            Self::Pow => java_pow(a, b),
+            Self::Equal => bool((a - b).abs() < 0.000001),
+            Self::NotEqual => bool((a - b).abs() >= 0.000001),
+            Self::StrictEqual => bool(a == b),
+            Self::StrictNotEqual => bool(a != b),
+            Self::LAnd => bool(a != 0. && b != 0.),
+            Self::LessThan => bool(a < b),
+            Self::LessThanEq => bool(a <= b),
+            Self::GreaterThan => bool(a > b),
+            Self::GreaterThanEq => bool(a >= b),
+            // Java only uses the lowest 6 bits of the shift, which is what wrapping shifts do
+            Self::Shl => long(|a, b| a.wrapping_shl(b as u32)),
+            Self::Shr => long(|a, b| a.wrapping_shr(b as u32)),
+            Self::UShr => long(|a, b| (a as u64).wrapping_shr(b as u32) as i64),
+            Self::Or => long(|a, b| a | b),
+            Self::BAnd => long(|a, b| a & b),
+            Self::Xor => long(|a, b| a ^ b),
+            Self::Flip => long(|a, _| !a),
+            Self::Max => java_max(a, b),
+            Self::Min => -java_max(-a, -b),
+            Self::Angle => angle(a as f32, b as f32).into(),
+            Self::AngleDiff => angle_dist(a as f32, b as f32).into(),
+            Self::Len => len(a as f32, b as f32).into(),
+            Self::Noise => simplex_raw2d(0, a, b).into(),
+            Self::Abs => a.abs(),
+            // Unlike `signum`, `Math.signum` keeps zeroes as they are
+            Self::Sign if a == 0. => a,
+            Self::Sign => a.signum(),
+            Self::Log => a.ln(),
+            Self::LogN => a.ln() / b.ln(),
+            Self::Log10 => a.log10(),
+            Self::Floor => a.floor(),
+            Self::Ceil => a.ceil(),
+            Self::Round => java_round(a),
+            Self::Sqrt => a.sqrt(),
+            Self::Rand => random * a,
+            Self::Sin => a.to_radians().sin(),
+            Self::Cos => a.to_radians().cos(),
+            Self::Tan => a.to_radians().tan(),
+            Self::ASin => a.asin().to_degrees(),
+            Self::ACos => a.acos().to_degrees(),
+            Self::ATan => a.atan().to_degrees(),
+        }
+    }
+}
+
+/// Checks the condition of a `jump` or `select` on two values, the same way the game does
+///
+/// # Examples
+///
+/// ```
+/// # use mlog_parse::{parser::args::ConditionOp, vm::{ops::condition, Value}};
+/// assert!(condition(ConditionOp::Equal, &Value::Null, &Value::Number(0.)));
+/// assert!(!condition(ConditionOp::StrictEqual, &Value::Null, &Value::Number(0.)));
+/// ```
+#[must_use]
+pub fn condition(op: ConditionOp, a: &Value, b: &Value) -> bool {
+    match op {
+        ConditionOp::Equal => a.loose_eq(b),
+        ConditionOp::NotEqual => !a.loose_eq(b),
+        ConditionOp::StrictEqual => a.strict_eq(b),
+        ConditionOp::StrictNotEqual => !a.strict_eq(b),
+        ConditionOp::LessThan => a.num() < b.num(),
+        ConditionOp::LessThanEq => a.num() <= b.num(),
+        ConditionOp::GreaterThan => a.num() > b.num(),
+        ConditionOp::GreaterThanEq => a.num() >= b.num(),
+        ConditionOp::Always => true,
+    }
+}
+
+/// `Math.pow`, which (unlike [`f64::powf`]) gives NaN for `1 ^ NaN` and `(-1) ^ ±∞`
+fn java_pow(a: f64, b: f64) -> f64 {
+    if b.is_nan() || (b.is_infinite() && a.abs() == 1.) {
+        f64::NAN
+    } else {
+        a.powf(b)
+    }
+}
+
+/// `Math.max`, which gives NaN if either side is NaN and treats 0 as bigger than -0
+fn java_max(a: f64, b: f64) -> f64 {
+    if a.is_nan() || b.is_nan() {
+        f64::NAN
+    } else if a == 0. && b == 0. {
+        if a.is_sign_negative() { b } else { a }
+    } else {
+        a.max(b)
+    }
+}
+
+/// `Math.round`, which rounds halves up and saturates to the range of a long
+pub(super) fn java_round(a: f64) -> f64 {
+    let floor = a.floor();
+    let rounded = if a - floor >= 0.5 { floor + 1. } else { floor };
+    // This also turns NaN into 0
+    rounded as i64 as f64
+}
+
+/// Arc's `Mathf.atan2`, which is an approximation that takes `x` first
+fn atan2(x: f32, y: f32) -> f32 {
+    use std::f32::consts::{FRAC_PI_2, PI};
+
+    if x == 0. {
+        return if y > 0. {
+            FRAC_PI_2
+        } else if y == 0. {
+            0.
+        } else {
+            -FRAC_PI_2
+        };
+    }
+
+    let z = y / x;
+    if z.abs() < 1. {
+        let atan = z / (1. + 0.28 * z * z);
+        if x < 0. {
+            atan + if y < 0. { -PI } else { PI }
+        } else {
+            atan
+        }
+    } else {
+        let atan = FRAC_PI_2 - z / (z * z + 0.28);
+        if y < 0. { atan - PI } else { atan }
+    }
+}
+
+/// Arc's `Angles.angle`
+fn angle(x: f32, y: f32) -> f32 {
+    let angle = atan2(x, y) * (180. / std::f32::consts::PI);
+    if angle < 0. { angle + 360. } else { angle }
+}
+
+/// Arc's `Angles.angleDist`
+fn angle_dist(a: f32, b: f32) -> f32 {
+    let modulo = |x: f32| ((x % 360.) + 360.) % 360.;
+    let (a, b) = (modulo(a), modulo(b));
+    let wrap = |x: f32| if x < 0. { x + 360. } else { x };
+
+    wrap(a - b).min(wrap(b - a))
+}
+
+/// Arc's `Mathf.dst`
+fn len(x: f32, y: f32) -> f32 {
+    f64::from(x * x + y * y).sqrt() as f32
+}
+
+/// Arc's `Simplex.raw2d`
+fn simplex_raw2d(seed: i32, x: f64, y: f64) -> f32 {
+    const GRAD: [[f64; 2]; 12] = [
+        [1., 1.],
+        [-1., 1.],
+        [1., -1.],
+        [-1., -1.],
+        [1., 0.],
+        [-1., 0.],
+        [1., 0.],
+        [-1., 0.],
+        [0., 1.],
+        [0., -1.],
+        [0., 1.],
+        [0., -1.],
+    ];
+
+    let sqrt3 = 3f64.sqrt();
+    let f2 = 0.5 * (sqrt3 - 1.);
+    let g2 = (3. - sqrt3) / 6.;
+
+    // Java ints, so everything wraps and `>>>` is an unsigned shift
+    let perm = |x: i32| {
+        let mix = |x: u32, by: u32| ((x >> 16) ^ x).wrapping_mul(by);
+        let x = mix(x as u32, 0x045d_9f3b);
+        let x = mix(x, 0x045d_9f3b_u32.wrapping_add(seed as u32));
+        (((x >> 16) ^ x) & 0xff) as i32
+    };
+    let fast_floor = |x: f64| {
+        let xi = x as i32;
+        if x < f64::from(xi) { xi - 1 } else { xi }
+    };
+    let corner = |gi: i32, x: f64, y: f64| {
+        let t = 0.5 - x * x - y * y;
+        if t < 0. {
+            0.
+        } else {
+            let grad = GRAD[gi as usize];
+            t * t * t * t * (grad[0] * x + grad[1] * y)
+        }
+    };
+
+    let s = (x + y) * f2;
+    let i = fast_floor(x + s);
+    let j = fast_floor(y + s);
+    let t = f64::from(i.wrapping_add(j)) * g2;
+    let x0 = x - (f64::from(i) - t);
+    let y0 = y - (f64::from(j) - t);
+
+    let (i1, j1) = if x0 > y0 { (1, 0) } else { (0, 1) };
+    let x1 = x0 - f64::from(i1) + g2;
+    let y1 = y0 - f64::from(j1) + g2;
+    let x2 = x0 - 1. + 2. * g2;
+    let y2 = y0 - 1. + 2. * g2;
+
+    let ii = i & 255;
+    let jj = j & 255;
+    let gi0 = perm(ii + perm(jj)) % 12;
+    let gi1 = perm(ii + i1 + perm(jj + j1)) % 12;
+    let gi2 = perm(ii + 1 + perm(jj + 1)) % 12;
+
+    (70. * (corner(gi0, x0, y0) + corner(gi1, x1, y1) + corner(gi2, x2, y2))) as f32
+}
@@ -0,0 +1,243 @@
+use std::collections::HashMap;
+use std::fmt;
+
+/// Counts of how often every instruction ran, made with [`Processor::start_profile`].
+///
+/// Every instruction takes `1 / ipt` of a tick, so the time spent on an instruction is how often
+/// it ran divided by the processor's instructions per tick (at the time).
+///
+/// [`Processor::start_profile`]: super::Processor::start_profile
+///
+/// # Examples
+///
+/// ```
+/// # use mlog_parse::{program::Program, parser::statements::Statement, vm::Processor};
+/// const SRC: &str = r#"
+///     set i 0
+///     loop:
+///         op add i i 1
+///     jump loop lessThan i 4
+///     stop
+/// "#;
+///
+/// let program = Program::<Statement>::parse(SRC).unwrap();
+/// let labels = program.labels().clone();
+/// let mut processor = Processor::from(program);
+/// processor.start_profile();
+/// processor.advance(2);
+///
+/// let profile = processor.take_profile().unwrap();
+/// assert_eq!(profile.hits(1), 4);
+/// assert_eq!(profile.branch(2), Some((3, 1)));
+///
+/// let report = profile.report(processor.statements(), &labels);
+/// assert_eq!(report.labels[0].label, "loop");
+/// // 9 instructions ran after `loop`, at 8 instructions per tick
+/// assert_eq!(report.labels[0].ticks, 9. / 8.);
+/// assert!(report.to_string().ends_with("total: 10 instructions in 1.25 ticks"));
+/// ```
+#[derive(Debug, PartialEq, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Profile {
+    instructions: Vec<Counts>,
+}
+
+/// The counts for a single instruction
+#[derive(Debug, PartialEq, Clone, Copy, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+struct Counts {
+    hits: u64,
+    ticks: f64,
+    /// How often it jumped and didn't, if it's a jump
+    branch: Option<(u64, u64)>,
+}
+
+/// A summary of a [`Profile`], with the instructions and labels it's about
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Report {
+    /// Every instruction, in order
+    pub instructions: Vec<InstructionReport>,
+    /// Every label, in the order they are in the program
+    pub labels: Vec<LabelReport>,
+    /// The number of instructions that ran
+    pub hits: u64,
+    /// The time spent running them, in ticks
+    pub ticks: f64,
+}
+
+/// How often an instruction ran
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct InstructionReport {
+    /// The index of the instruction
+    pub index: usize,
+    /// The instruction, as mlog
+    pub source: String,
+    /// The number of times it ran
+    pub hits: u64,
+    /// The time spent running it, in ticks
+    pub ticks: f64,
+    /// How often it jumped and how often it didn't, if it's a jump
+    pub branch: Option<(u64, u64)>,
+}
+
+/// The time spent in the code after a label
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct LabelReport {
+    /// The name of the label
+    pub label: String,
+    /// The instructions from this label up to the next one
+    pub range: std::ops::Range<usize>,
+    /// The number of instructions that ran in the range
+    pub hits: u64,
+    /// The time spent in the range, in ticks
+    pub ticks: f64,
+}
+
+impl Profile {
+    /// Creates an empty profile for a program with `len` instructions
+    #[must_use]
+    pub fn new(len: usize) -> Self {
+        Self {
+            instructions: vec![Counts::default(); len],
+        }
+    }
+
+    /// Gets how often the instruction at `index` ran
+    #[must_use]
+    pub fn hits(&self, index: usize) -> u64 {
+        self.instructions.get(index).map_or(0, |x| x.hits)
+    }
+
+    /// Gets the time spent running the instruction at `index`, in ticks
+    #[must_use]
+    pub fn ticks(&self, index: usize) -> f64 {
+        self.instructions.get(index).map_or(0., |x| x.ticks)
+    }
+
+    /// Gets how often the jump at `index` jumped and how often it didn't. Returns `None` if it
+    /// isn't a jump or never ran.
+    #[must_use]
+    pub fn branch(&self, index: usize) -> Option<(u64, u64)> {
+        self.instructions.get(index)?.branch
+    }
+
+    /// Summarises the profile. `statements` and `labels` should be from the program that was
+    /// profiled, like from [`Program::labels`]. Instructions before the first label aren't
+    /// counted towards any label.
+    ///
+    /// [`Program::labels`]: crate::program::Program::labels
+    #[must_use]
+    pub fn report<S: fmt::Display>(
+        &self,
+        statements: &[S],
+        labels: &HashMap<&str, usize>,
+    ) -> Report {
+        let instructions: Vec<_> = statements
+            .iter()
+            .enumerate()
+            .map(|(index, statement)| {
+                let counts = self.instructions.get(index).copied().unwrap_or_default();
+                InstructionReport {
+                    index,
+                    source: statement.to_string(),
+                    hits: counts.hits,
+                    ticks: counts.ticks,
+                    branch: counts.branch,
+                }
+            })
+            .collect();
+
+        let mut sorted: Vec<_> = labels.iter().map(|(k, v)| (*v, *k)).collect();
+        sorted.sort_unstable();
+
+        let labels = sorted
+            .iter()
+            .map(|&(start, label)| {
+                // Labels on the same instruction share the code after it
+                let end = sorted
+                    .iter()
+                    .map(|x| x.0)
+                    .find(|&x| x > start)
+                    .unwrap_or(instructions.len())
+                    .max(start);
+                let range = start..end;
+                let in_range = instructions.get(range.clone()).unwrap_or_default();
+
+                LabelReport {
+                    label: label.to_string(),
+                    hits: in_range.iter().map(|x| x.hits).sum(),
+                    ticks: in_range.iter().map(|x| x.ticks).sum(),
+                    range,
+                }
+            })
+            .collect();
+
+        Report {
+            hits: instructions.iter().map(|x| x.hits).sum(),
+            ticks: instructions.iter().map(|x| x.ticks).sum(),
+            instructions,
+            labels,
+        }
+    }
+
+    /// Counts a run of the instruction at `index`, taking `ticks` ticks
+    pub(super) fn hit(&mut self, index: usize, ticks: f64) {
+        if let Some(counts) = self.instructions.get_mut(index) {
+            counts.hits += 1;
+            counts.ticks += ticks;
+        }
+    }
+
+    /// Counts whether the jump at `index` jumped
+    pub(super) fn jump(&mut self, index: usize, taken: bool) {
+        if let Some(counts) = self.instructions.get_mut(index) {
+            let (yes, no) = counts.branch.get_or_insert_default();
+            if taken {
+                *yes += 1;
+            } else {
+                *no += 1;
+            }
+        }
+    }
+}
+
+impl fmt::Display for Report {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "{:>5} {:>10} {:>10} {:>15}  instruction",
+            "index", "hits", "ticks", "taken/not"
+        )?;
+        for x in &self.instructions {
+            let branch = x
+                .branch
+                .map(|(yes, no)| format!("{yes}/{no}"))
+                .unwrap_or_default();
+            writeln!(
+                f,
+                "{:>5} {:>10} {:>10.2} {:>15}  {}",
+                x.index, x.hits, x.ticks, branch, x.source
+            )?;
+        }
+
+        writeln!(f)?;
+        writeln!(f, "{:>11} {:>10} {:>10}  label", "range", "hits", "ticks")?;
+        for x in &self.labels {
+            let range = format!("{}..{}", x.range.start, x.range.end);
+            writeln!(
+                f,
+                "{:>11} {:>10} {:>10.2}  {}",
+                range, x.hits, x.ticks, x.label
+            )?;
+        }
+
+        write!(
+            f,
+            "total: {} instructions in {:.2} ticks",
+            self.hits, self.ticks
+        )
+    }
+}
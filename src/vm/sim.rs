@@ -0,0 +1,191 @@
+use super::world::{NoWorld, World};
+use super::{Block, Processor, blocks::Memory};
+use crate::parser::statements::WprocStatement;
+
+/// A processor in a [`Simulation`], which can run both normal and world processor statements
+pub type SimProcessor<'a, W> = Processor<'a, W, WprocStatement<'a>>;
+
+/// Several processors running side by side, sharing the blocks they're linked to.
+///
+/// Each processor has its own link table, so the same block can be `cell1` to one processor and
+/// `bank1` to another. Like in the game, processors take turns every tick: each one runs its
+/// instructions for the tick before the next one starts, in the order they were added.
+///
+/// Shared blocks only live in the simulation between ticks, so they should be looked at with
+/// [`Simulation::block`] instead of through the processors.
+///
+/// # Examples
+///
+/// ```
+/// # use mlog_parse::vm::{Processor, Simulation, blocks::Memory};
+/// let producer = Processor::parse("op add n n 1\nwrite n cell1 0").unwrap();
+/// let consumer = Processor::parse("read n bank1 0\nop max most most n").unwrap();
+///
+/// let mut sim = Simulation::new();
+/// sim.add_block("shared", Memory::cell());
+/// let producer = sim.add_processor(producer);
+/// let consumer = sim.add_processor(consumer);
+/// sim.link(producer, "cell1", "shared");
+/// sim.link(consumer, "bank1", "shared");
+/// sim.advance(10);
+///
+/// assert_eq!(sim.memory("shared").unwrap().read(0.), 40.);
+/// assert_eq!(sim.processor(consumer).get("most").num(), 40.);
+/// ```
+#[derive(Debug, Clone)]
+pub struct Simulation<'a, W = NoWorld> {
+    /// The shared blocks, which are `None` while a processor has them for its turn
+    blocks: Vec<(String, Option<Block>)>,
+    processors: Vec<SimProcessor<'a, W>>,
+    /// The links of every processor, as the name the processor uses and the name of the block
+    links: Vec<Vec<(String, String)>>,
+    tick: u64,
+}
+
+impl<W: World> Default for Simulation<'_, W> {
+    fn default() -> Self {
+        Self {
+            blocks: Vec::new(),
+            processors: Vec::new(),
+            links: Vec::new(),
+            tick: 0,
+        }
+    }
+}
+
+impl<'a, W: World> Simulation<'a, W> {
+    /// Creates a simulation without any processors or blocks
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a block that processors can be linked to, replacing any block with the same name
+    pub fn add_block(&mut self, name: impl Into<String>, block: impl Into<Block>) {
+        let (name, block) = (name.into(), Some(block.into()));
+        match self.blocks.iter_mut().find(|x| x.0 == name) {
+            Some(x) => x.1 = block,
+            None => self.blocks.push((name, block)),
+        }
+    }
+
+    /// Gets a block
+    #[must_use]
+    pub fn block(&self, name: &str) -> Option<&Block> {
+        self.blocks
+            .iter()
+            .find(|x| x.0 == name)
+            .and_then(|x| x.1.as_ref())
+    }
+
+    /// Gets a mutable reference to a block
+    pub fn block_mut(&mut self, name: &str) -> Option<&mut Block> {
+        self.blocks
+            .iter_mut()
+            .find(|x| x.0 == name)
+            .and_then(|x| x.1.as_mut())
+    }
+
+    /// Gets the memory of a memory cell or bank
+    #[must_use]
+    pub fn memory(&self, name: &str) -> Option<&Memory> {
+        self.block(name).and_then(Block::as_memory)
+    }
+
+    /// Gets the text of a message block
+    #[must_use]
+    pub fn message(&self, name: &str) -> Option<&str> {
+        self.block(name).and_then(Block::as_message)
+    }
+
+    /// Adds a processor, which starts running on the next tick. Returns its index, which is used
+    /// to link it and look at it later.
+    pub fn add_processor(&mut self, processor: impl Into<SimProcessor<'a, W>>) -> usize {
+        let mut processor = processor.into();
+        processor.set_tick(self.tick);
+        self.processors.push(processor);
+        self.links.push(Vec::new());
+        self.processors.len() - 1
+    }
+
+    /// Links the block called `block` to a processor, which sees it as `name`. The block doesn't
+    /// have to exist yet, and the processor can't use it until it does. Like in the game, a
+    /// processor can only link a block once, so linking it again under another name unlinks the
+    /// old one.
+    ///
+    /// # Panics
+    ///
+    /// Panics if there is no processor at `processor`.
+    pub fn link(&mut self, processor: usize, name: impl Into<String>, block: impl Into<String>) {
+        let (name, block) = (name.into(), block.into());
+        // Taken up now so that the order of `getlink` doesn't change
+        *self.processors[processor].link_slot(&name) = None;
+
+        let links = &mut self.links[processor];
+        links.retain(|x| x.0 != name && x.1 != block);
+        links.push((name, block));
+    }
+
+    /// Gets a processor
+    ///
+    /// # Panics
+    ///
+    /// Panics if there is no processor at `processor`.
+    #[must_use]
+    pub fn processor(&self, processor: usize) -> &SimProcessor<'a, W> {
+        &self.processors[processor]
+    }
+
+    /// Gets a processor mutably
+    ///
+    /// # Panics
+    ///
+    /// Panics if there is no processor at `processor`.
+    pub fn processor_mut(&mut self, processor: usize) -> &mut SimProcessor<'a, W> {
+        &mut self.processors[processor]
+    }
+
+    /// Gets every processor, in the order they run in
+    #[must_use]
+    pub fn processors(&self) -> &[SimProcessor<'a, W>] {
+        &self.processors
+    }
+
+    /// Gets the number of ticks that have passed
+    #[must_use]
+    pub fn tick(&self) -> u64 {
+        self.tick
+    }
+
+    /// Runs every processor for `ticks` ticks
+    pub fn advance(&mut self, ticks: u64) {
+        for _ in 0..ticks {
+            self.run_tick();
+        }
+    }
+
+    /// Runs every processor for a single tick, one after the other
+    pub fn run_tick(&mut self) {
+        for (processor, links) in self.processors.iter_mut().zip(&self.links) {
+            Self::swap_links(&mut self.blocks, processor, links);
+            processor.set_tick(self.tick);
+            processor.run_tick();
+            Self::swap_links(&mut self.blocks, processor, links);
+        }
+        self.tick += 1;
+    }
+
+    /// Swaps the shared blocks with the ones in a processor's link table, to give them to the
+    /// processor for its turn and to take them back after
+    fn swap_links(
+        blocks: &mut [(String, Option<Block>)],
+        processor: &mut SimProcessor<'a, W>,
+        links: &[(String, String)],
+    ) {
+        for (name, block) in links {
+            if let Some(shared) = blocks.iter_mut().find(|x| x.0 == *block) {
+                std::mem::swap(&mut shared.1, processor.link_slot(name));
+            }
+        }
+    }
+}
@@ -0,0 +1,98 @@
+use super::world::World;
+use super::{Block, Instruction, Processor, Value, display::DrawCommand};
+use std::collections::HashMap;
+
+/// Everything a [`Processor`] has changed while running, made with [`Processor::snapshot`].
+///
+/// Snapshots don't hold the program or the world around the processor, so they're restored onto
+/// a processor running the same program. With the `serde` feature they can be saved, to pick a
+/// long simulation up again later or to attach a failing run to a bug report.
+///
+/// # Examples
+///
+/// ```
+/// # use mlog_parse::vm::{Processor, Value, blocks::Memory};
+/// const SRC: &str = r#"
+///     op add i i 1
+///     write i cell1 i
+///     print i
+/// "#;
+///
+/// let mut processor = Processor::parse(SRC).unwrap();
+/// processor.link("cell1", Memory::cell());
+/// processor.advance(1);
+/// let checkpoint = processor.snapshot();
+/// processor.advance(1);
+///
+/// let mut restored = Processor::parse(SRC).unwrap();
+/// restored.restore(checkpoint);
+/// assert_eq!(restored.get("i"), Value::Number(3.));
+/// assert_eq!(restored.counter(), 2);
+/// assert_eq!(restored.memory("cell1").unwrap().read(3.), 3.);
+///
+/// restored.advance(1);
+/// assert_eq!(restored.snapshot(), processor.snapshot());
+/// ```
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Snapshot {
+    /// The value of every variable
+    pub variables: HashMap<String, Value>,
+    /// The value of `@counter`
+    pub counter: usize,
+    /// The linked blocks and what's in them, in the order they were linked. Names that are
+    /// linked without a block have `None`.
+    pub links: Vec<(String, Option<Block>)>,
+    /// The text that hasn't been flushed yet
+    pub text_buffer: String,
+    /// The drawing commands that haven't been flushed yet
+    pub graphics_buffer: Vec<DrawCommand>,
+    /// The state of the random number generator
+    pub rng: u64,
+    /// The bound unit
+    pub unit: Value,
+    /// The number of instructions run every tick
+    pub ipt: f64,
+    /// The instructions saved up for later ticks
+    pub accumulator: f64,
+    /// The number of ticks that have passed
+    pub tick: u64,
+    /// The tick that every unfinished `wait` finishes on, by the index of the `wait`
+    pub waits: HashMap<usize, f64>,
+}
+
+impl<'a, W: World, S: Instruction<'a>> Processor<'a, W, S> {
+    /// Saves everything that changes while the processor runs
+    #[must_use]
+    pub fn snapshot(&self) -> Snapshot {
+        Snapshot {
+            variables: self.variables.clone(),
+            counter: self.counter,
+            links: self.links.clone(),
+            text_buffer: self.text_buffer.clone(),
+            graphics_buffer: self.graphics_buffer.clone(),
+            rng: self.rng,
+            unit: self.unit.clone(),
+            ipt: self.ipt,
+            accumulator: self.accumulator,
+            tick: self.tick,
+            waits: self.waits.clone(),
+        }
+    }
+
+    /// Puts the processor back to how it was when a snapshot was made, keeping its program,
+    /// world and any trace or profile being recorded
+    pub fn restore(&mut self, snapshot: Snapshot) {
+        self.variables = snapshot.variables;
+        self.counter = snapshot.counter;
+        self.links = snapshot.links;
+        self.text_buffer = snapshot.text_buffer;
+        self.graphics_buffer = snapshot.graphics_buffer;
+        self.rng = snapshot.rng;
+        self.unit = snapshot.unit;
+        self.ipt = snapshot.ipt;
+        self.accumulator = snapshot.accumulator;
+        self.tick = snapshot.tick;
+        self.waits = snapshot.waits;
+    }
+}
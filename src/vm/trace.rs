@@ -0,0 +1,174 @@
+use super::world::{ControlCommand, UnitCommand, World, WorldEvent};
+use super::{Instruction, Processor, Value, display::DrawCommand};
+
+/// A record of every instruction a processor ran, made with [`Processor::start_trace`].
+///
+/// Traces only hold what changed, so they stay small enough to save (with the `serde` feature)
+/// and compare later. [`Trace::replay`] checks that a processor still runs the same way, and
+/// [`Trace::effects`] can be compared between programs that do the same thing with different
+/// instructions, like the optimised and unoptimised builds of the same code.
+///
+/// # Examples
+///
+/// ```
+/// # use mlog_parse::vm::{Processor, Value, blocks::Memory, trace::Effect};
+/// const SRC: &str = r#"
+///     op add i i 1
+///     write i cell1 0
+/// "#;
+///
+/// let mut processor = Processor::parse(SRC).unwrap();
+/// processor.link("cell1", Memory::cell());
+/// processor.start_trace();
+/// for _ in 0..4 {
+///     processor.step();
+/// }
+/// let trace = processor.take_trace().unwrap();
+///
+/// assert_eq!(trace.steps().len(), 4);
+/// assert_eq!(trace.steps()[2].writes, [("i".to_string(), Value::Number(2.))]);
+/// assert_eq!(
+///     trace.effects().last(),
+///     Some(&Effect::Write { cell: "cell1".into(), index: 0., value: 2. })
+/// );
+///
+/// let mut fresh = Processor::parse(SRC).unwrap();
+/// fresh.link("cell1", Memory::cell());
+/// assert_eq!(trace.replay(&mut fresh), Ok(()));
+/// ```
+#[derive(Debug, PartialEq, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Trace {
+    steps: Vec<TraceStep>,
+}
+
+/// A single instruction in a [`Trace`]
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TraceStep {
+    /// The index of the instruction
+    pub index: usize,
+    /// The value of `@counter` after it ran
+    pub counter: usize,
+    /// The tick it ran on
+    pub tick: u64,
+    /// The variables it wrote to, in order
+    pub writes: Vec<(String, Value)>,
+    /// What it changed outside of the processor, in order
+    pub effects: Vec<Effect>,
+}
+
+/// Something an instruction changed outside of the processor's variables
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Effect {
+    /// A value was written to a memory cell or bank
+    Write {
+        /// The name of the memory block
+        cell: String,
+        /// The index that was written to
+        index: f64,
+        /// The value that was written
+        value: f64,
+    },
+    /// The text buffer was flushed, to a message block or to nothing
+    PrintFlush {
+        /// Where it was flushed to
+        output: Value,
+        /// The text that was flushed
+        text: String,
+    },
+    /// The graphics buffer was flushed, to a display or to nothing
+    DrawFlush {
+        /// Where it was flushed to
+        output: Value,
+        /// The commands that were flushed
+        commands: Vec<DrawCommand>,
+    },
+    /// A unit was bound
+    UnitBind(Value),
+    /// The bound unit was given a command
+    UnitControl(UnitCommand),
+    /// A building was given a command
+    Control {
+        /// The building
+        block: Value,
+        /// The command
+        command: ControlCommand,
+    },
+    /// A world processor changed the world
+    World(WorldEvent),
+}
+
+/// Where a replayed processor stopped behaving like its [`Trace`]
+#[derive(Debug, PartialEq, Clone)]
+pub struct Divergence {
+    /// The number of steps that matched before this one
+    pub step: usize,
+    /// The step in the trace
+    pub expected: TraceStep,
+    /// What the processor did instead, which is nothing if it has no statements
+    pub found: Option<TraceStep>,
+}
+
+impl Trace {
+    /// Gets every step, in the order they ran
+    #[must_use]
+    pub fn steps(&self) -> &[TraceStep] {
+        &self.steps
+    }
+
+    /// Gets every side effect, in the order they happened
+    pub fn effects(&self) -> impl Iterator<Item = &Effect> {
+        self.steps.iter().flat_map(|x| &x.effects)
+    }
+
+    /// Runs a processor for as many steps as there are in the trace, checking that each one does
+    /// the same thing. The processor's clock is set to the one in the trace before each step, so
+    /// traces made while running tick by tick can be replayed too.
+    ///
+    /// # Errors
+    ///
+    /// Returns the first step that was different.
+    pub fn replay<'a, W: World, S: Instruction<'a>>(
+        &self,
+        processor: &mut Processor<'a, W, S>,
+    ) -> Result<(), Box<Divergence>> {
+        let previous = processor.trace.replace(Self::default());
+
+        let mut result = Ok(());
+        for (i, expected) in self.steps.iter().enumerate() {
+            processor.set_tick(expected.tick);
+            processor.step();
+
+            let found = processor.trace.as_mut().and_then(|x| x.steps.pop());
+            if found.as_ref() != Some(expected) {
+                result = Err(Box::new(Divergence {
+                    step: i,
+                    expected: expected.clone(),
+                    found,
+                }));
+                break;
+            }
+        }
+
+        processor.trace = previous;
+        result
+    }
+
+    /// Adds a step that's about to run
+    pub(super) fn start_step(&mut self, index: usize, tick: u64) {
+        self.steps.push(TraceStep {
+            index,
+            counter: index,
+            tick,
+            writes: Vec::new(),
+            effects: Vec::new(),
+        });
+    }
+
+    /// Gets the step that's running
+    pub(super) fn current(&mut self) -> Option<&mut TraceStep> {
+        self.steps.last_mut()
+    }
+}
@@ -0,0 +1,178 @@
+use super::ops::java_round;
+use std::fmt;
+
+/// A value stored in a variable at runtime.
+///
+/// Like in the game, a value is either a number or an object. Objects are `null`, strings,
+/// content (like `@copper`), buildings and units. Objects are 1 when used as a number (or 0 for
+/// `null`), and can only be equal to other objects if they're the same object.
+///
+/// # Examples
+///
+/// ```
+/// # use mlog_parse::vm::Value;
+/// let copper = Value::Content("copper".into());
+///
+/// assert_eq!(copper.num(), 1.);
+/// assert!(copper.loose_eq(&Value::Number(1.)));
+/// assert!(!copper.strict_eq(&Value::Number(1.)));
+/// assert!(!copper.loose_eq(&Value::Content("lead".into())));
+/// assert!(!Value::Null.is_truthy());
+/// ```
+#[derive(Debug, PartialEq, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Value {
+    /// `null`, which is also the value of every variable before it's set
+    #[default]
+    Null,
+    /// A number. This is never NaN or infinite, since those are turned into [`Value::Null`].
+    Number(f64),
+    /// A string
+    String(String),
+    /// A content constant, by its name without the `@` (e.g. `copper`, `router`, `flare` or
+    /// `sharded`). This also covers the other named constants, like sensor properties
+    /// (`@health`).
+    Content(String),
+    /// A building, by the name the processor links it as (e.g. `cell1`)
+    Building(String),
+    /// A unit, by its id
+    Unit(u32),
+}
+
+impl Value {
+    /// Creates a number, turning NaN and infinite values into `null` like the game does
+    #[must_use]
+    pub fn number(x: f64) -> Self {
+        if x.is_finite() {
+            Self::Number(x)
+        } else {
+            Self::Null
+        }
+    }
+
+    /// Creates a number from a boolean (1 for `true` and 0 for `false`)
+    #[must_use]
+    pub fn bool(x: bool) -> Self {
+        Self::Number(if x { 1. } else { 0. })
+    }
+
+    /// Gets the numeric value. Objects (like strings) are 1, and `null` is 0.
+    #[must_use]
+    pub fn num(&self) -> f64 {
+        match self {
+            Self::Null => 0.,
+            Self::Number(x) => *x,
+            _ => 1.,
+        }
+    }
+
+    /// Checks if the value counts as true. Objects are true unless they're `null`, and numbers
+    /// are true unless they're (very close to) 0.
+    #[must_use]
+    pub fn is_truthy(&self) -> bool {
+        match self {
+            Self::Null => false,
+            Self::Number(x) => x.abs() >= 0.00001,
+            _ => true,
+        }
+    }
+
+    /// Checks if this is an object (anything other than a number, including `null`)
+    #[must_use]
+    pub fn is_obj(&self) -> bool {
+        !matches!(self, Self::Number(_))
+    }
+
+    /// Compares two values like `equal` does: objects are compared with each other directly, and
+    /// anything else is compared by its numeric value, allowing for a small error.
+    #[must_use]
+    pub fn loose_eq(&self, other: &Self) -> bool {
+        if self.is_obj() && other.is_obj() {
+            self == other
+        } else {
+            (self.num() - other.num()).abs() < 0.000001
+        }
+    }
+
+    /// Compares two values like `strictEqual` does, with no conversions between types
+    #[must_use]
+    pub fn strict_eq(&self, other: &Self) -> bool {
+        self == other
+    }
+}
+
+impl From<f64> for Value {
+    fn from(x: f64) -> Self {
+        Self::number(x)
+    }
+}
+
+impl From<bool> for Value {
+    fn from(x: bool) -> Self {
+        Self::bool(x)
+    }
+}
+
+impl From<&str> for Value {
+    fn from(x: &str) -> Self {
+        Self::String(x.to_string())
+    }
+}
+
+impl From<String> for Value {
+    fn from(x: String) -> Self {
+        Self::String(x)
+    }
+}
+
+impl fmt::Display for Value {
+    /// Formats the value the way `print` does. Numbers within 0.00001 of an integer are printed
+    /// as that integer, and other numbers are printed like Java prints doubles (e.g. `0.1` or
+    /// `1.0E-5`).
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Null => f.write_str("null"),
+            Self::Number(x) => {
+                let rounded = java_round(*x);
+                if (x - rounded).abs() < 0.00001 {
+                    write!(f, "{}", rounded as i64)
+                } else {
+                    write_java_double(f, *x)
+                }
+            }
+            Self::String(x) | Self::Content(x) | Self::Building(x) => f.write_str(x),
+            Self::Unit(x) => write!(f, "unit#{x}"),
+        }
+    }
+}
+
+/// Writes a number like Java's `Double.toString`: plain decimals between 0.001 and 10 million, and
+/// scientific notation with an `E` otherwise, always with at least one digit after the point.
+fn write_java_double(f: &mut fmt::Formatter<'_>, x: f64) -> fmt::Result {
+    if x < 0. {
+        f.write_str("-")?;
+    }
+
+    // Rust finds the same shortest digits that round trip as Java does
+    let sci = format!("{:e}", x.abs());
+    let (mantissa, exponent) = sci.split_once('e').unwrap_or((&sci, "0"));
+    let exponent: i32 = exponent.parse().unwrap_or(0);
+    let digits = mantissa.replace('.', "");
+
+    if (-3..7).contains(&exponent) {
+        if exponent < 0 {
+            let zeros = "0".repeat((-exponent - 1) as usize);
+            write!(f, "0.{zeros}{digits}")
+        } else {
+            let point = exponent as usize + 1;
+            let padded = format!("{digits:0<point$}");
+            let (whole, fraction) = padded.split_at(point);
+            let fraction = if fraction.is_empty() { "0" } else { fraction };
+            write!(f, "{whole}.{fraction}")
+        }
+    } else {
+        let (first, rest) = digits.split_at(1);
+        let rest = if rest.is_empty() { "0" } else { rest };
+        write!(f, "{first}.{rest}E{exponent}")
+    }
+}
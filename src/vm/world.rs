@@ -0,0 +1,704 @@
+use crate::vm::Value;
+use std::cell::RefCell;
+use std::rc::Rc;
+
+/// The game world around a processor, which answers the instructions that look at or change it
+/// (like `sensor`, `radar`, `ucontrol` and `control`).
+///
+/// Every method has a default that acts like an empty world, so only the parts a test needs have
+/// to be written.
+///
+/// # Examples
+///
+/// ```
+/// # use mlog_parse::vm::{Processor, Value, world::World};
+/// struct Miner;
+///
+/// impl World for Miner {
+///     fn unit_bind(&mut self, unit_type: &Value) -> Value {
+///         Value::Unit(7)
+///     }
+///
+///     fn sensor(&mut self, target: &Value, property: &Value) -> Value {
+///         match (target, property) {
+///             (Value::Unit(7), Value::Content(x)) if x == "totalItems" => Value::Number(20.),
+///             _ => Value::Null,
+///         }
+///     }
+/// }
+///
+/// let src = "ubind @mono\nsensor items @unit @totalItems";
+/// let mut processor = Processor::parse(src).unwrap().with_world(Miner);
+/// processor.run(2);
+///
+/// assert_eq!(processor.get("items"), Value::Number(20.));
+/// ```
+pub trait World {
+    /// Reads a property of a building, unit or other object for `sensor`
+    fn sensor(&mut self, target: &Value, property: &Value) -> Value {
+        let _ = (target, property);
+        Value::Null
+    }
+
+    /// Finds a unit for `radar` and `uradar`, giving `null` if there aren't any
+    fn radar(&mut self, query: &RadarQuery) -> Value {
+        let _ = query;
+        Value::Null
+    }
+
+    /// Gets the building linked to the processor at `index` for `getlink`. Returning `None` falls
+    /// back to the blocks linked with [`Processor::link`](super::Processor::link).
+    fn link(&mut self, index: usize) -> Option<Value> {
+        let _ = index;
+        None
+    }
+
+    /// Gets the number of buildings linked to the processor for `@links`. Returning `None` falls
+    /// back to the number of blocks linked with [`Processor::link`](super::Processor::link).
+    fn link_count(&self) -> Option<usize> {
+        None
+    }
+
+    /// Picks the unit to bind for `ubind`, which is `null` if there aren't any. `unit_type` is
+    /// usually a unit type (like `@flare`), but can also be a unit to bind directly.
+    fn unit_bind(&mut self, unit_type: &Value) -> Value {
+        let _ = unit_type;
+        Value::Null
+    }
+
+    /// Runs a `ucontrol` command on the bound unit. This is only called when a unit is bound.
+    ///
+    /// Returns the values of the instruction's outputs in order, which only `within` and
+    /// `getBlock` have. Missing outputs are set to `null`.
+    fn unit_control(&mut self, unit: &Value, command: &UnitCommand) -> Vec<Value> {
+        let _ = (unit, command);
+        Vec::new()
+    }
+
+    /// Searches for a building or ore near the bound unit for `ulocate`. This is only called when
+    /// a unit is bound.
+    fn unit_locate(&mut self, unit: &Value, query: &LocateQuery) -> Option<Located> {
+        let _ = (unit, query);
+        None
+    }
+
+    /// Runs a `control` command on a building
+    fn control(&mut self, block: &Value, command: &ControlCommand) {
+        let _ = (block, command);
+    }
+
+    /// Changes the world for an instruction only world processors have (like `setblock` or
+    /// `spawn`). Returns the unit created by [`WorldEvent::SpawnUnit`], and is ignored otherwise.
+    fn apply(&mut self, event: &WorldEvent) -> Value {
+        let _ = event;
+        Value::Null
+    }
+
+    /// Looks something up for an instruction only world processors have (like `getblock` or
+    /// `fetch`)
+    fn query(&mut self, query: &WorldQuery) -> Value {
+        let _ = query;
+        Value::Null
+    }
+}
+
+/// Shares a world between several processors, like the ones in a
+/// [`Simulation`](super::Simulation)
+impl<W: World> World for Rc<RefCell<W>> {
+    fn sensor(&mut self, target: &Value, property: &Value) -> Value {
+        self.borrow_mut().sensor(target, property)
+    }
+
+    fn radar(&mut self, query: &RadarQuery) -> Value {
+        self.borrow_mut().radar(query)
+    }
+
+    fn link(&mut self, index: usize) -> Option<Value> {
+        self.borrow_mut().link(index)
+    }
+
+    fn link_count(&self) -> Option<usize> {
+        self.borrow().link_count()
+    }
+
+    fn unit_bind(&mut self, unit_type: &Value) -> Value {
+        self.borrow_mut().unit_bind(unit_type)
+    }
+
+    fn unit_control(&mut self, unit: &Value, command: &UnitCommand) -> Vec<Value> {
+        self.borrow_mut().unit_control(unit, command)
+    }
+
+    fn unit_locate(&mut self, unit: &Value, query: &LocateQuery) -> Option<Located> {
+        self.borrow_mut().unit_locate(unit, query)
+    }
+
+    fn control(&mut self, block: &Value, command: &ControlCommand) {
+        self.borrow_mut().control(block, command);
+    }
+
+    fn apply(&mut self, event: &WorldEvent) -> Value {
+        self.borrow_mut().apply(event)
+    }
+
+    fn query(&mut self, query: &WorldQuery) -> Value {
+        self.borrow_mut().query(query)
+    }
+}
+
+/// A world with nothing in it, which is what processors use unless they're given one
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
+pub struct NoWorld;
+
+impl World for NoWorld {}
+
+/// A world that keeps a log of every change a world processor makes to it, passing everything on
+/// to another world.
+///
+/// # Examples
+///
+/// ```
+/// # use mlog_parse::vm::{Processor, Value, world::{NoWorld, Recorder, WorldEvent}};
+/// let mut processor = Processor::parse_wproc("spawn @flare 10 20 90 @sharded unit")
+///     .unwrap()
+///     .with_world(Recorder::new(NoWorld));
+/// processor.run(1);
+///
+/// assert_eq!(
+///     processor.world().events(),
+///     [WorldEvent::SpawnUnit {
+///         unit_type: Value::Content("flare".into()),
+///         x: 10.,
+///         y: 20.,
+///         rotation: 90.,
+///         team: Value::Content("sharded".into()),
+///     }]
+/// );
+/// ```
+#[derive(Debug, PartialEq, Clone, Default)]
+pub struct Recorder<W = NoWorld> {
+    world: W,
+    events: Vec<WorldEvent>,
+}
+
+impl<W: World> Recorder<W> {
+    /// Creates a recorder wrapping another world
+    #[must_use]
+    pub fn new(world: W) -> Self {
+        Self {
+            world,
+            events: Vec::new(),
+        }
+    }
+
+    /// Gets every change made so far, oldest first
+    #[must_use]
+    pub fn events(&self) -> &[WorldEvent] {
+        &self.events
+    }
+
+    /// Takes the log, leaving it empty
+    pub fn take_events(&mut self) -> Vec<WorldEvent> {
+        std::mem::take(&mut self.events)
+    }
+
+    /// Gets the world being recorded
+    #[must_use]
+    pub fn inner(&self) -> &W {
+        &self.world
+    }
+
+    /// Gets the world being recorded mutably
+    pub fn inner_mut(&mut self) -> &mut W {
+        &mut self.world
+    }
+}
+
+impl<W: World> World for Recorder<W> {
+    fn sensor(&mut self, target: &Value, property: &Value) -> Value {
+        self.world.sensor(target, property)
+    }
+
+    fn radar(&mut self, query: &RadarQuery) -> Value {
+        self.world.radar(query)
+    }
+
+    fn link(&mut self, index: usize) -> Option<Value> {
+        self.world.link(index)
+    }
+
+    fn link_count(&self) -> Option<usize> {
+        self.world.link_count()
+    }
+
+    fn unit_bind(&mut self, unit_type: &Value) -> Value {
+        self.world.unit_bind(unit_type)
+    }
+
+    fn unit_control(&mut self, unit: &Value, command: &UnitCommand) -> Vec<Value> {
+        self.world.unit_control(unit, command)
+    }
+
+    fn unit_locate(&mut self, unit: &Value, query: &LocateQuery) -> Option<Located> {
+        self.world.unit_locate(unit, query)
+    }
+
+    fn control(&mut self, block: &Value, command: &ControlCommand) {
+        self.world.control(block, command);
+    }
+
+    fn apply(&mut self, event: &WorldEvent) -> Value {
+        self.events.push(event.clone());
+        self.world.apply(event)
+    }
+
+    fn query(&mut self, query: &WorldQuery) -> Value {
+        self.world.query(query)
+    }
+}
+
+/// What a `radar` or `uradar` instruction is looking for
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RadarQuery {
+    /// The building or unit searching
+    pub from: Value,
+    /// The filters a unit has to match, like `enemy`, `flying` or `any`
+    pub targets: [String; 3],
+    /// How units are compared, like `distance` or `health`
+    pub sort: String,
+    /// Whether the largest value is picked instead of the smallest
+    pub descending: bool,
+}
+
+/// What a `ulocate` instruction is looking for
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct LocateQuery {
+    /// The kind of thing to find: `building`, `ore`, `spawn` or `damaged`
+    pub find: String,
+    /// The group of building to find, like `core` or `turret`
+    pub group: String,
+    /// Whether to find enemy buildings instead of friendly ones
+    pub enemy: bool,
+    /// The ore to find
+    pub ore: Value,
+}
+
+/// Something found by `ulocate`
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Located {
+    /// The x position, in tiles
+    pub x: f64,
+    /// The y position, in tiles
+    pub y: f64,
+    /// The building found, or `null` for ores and spawns
+    pub building: Value,
+}
+
+/// A command given to a unit by `ucontrol`
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum UnitCommand {
+    /// `idle`, which stops moving but keeps building and mining
+    Idle,
+    /// `stop`, which stops moving, building and mining
+    Stop,
+    /// `move`
+    Move {
+        /// The x position, in tiles
+        x: f64,
+        /// The y position, in tiles
+        y: f64,
+    },
+    /// `approach`, which moves to within `radius` of a position
+    Approach {
+        /// The x position, in tiles
+        x: f64,
+        /// The y position, in tiles
+        y: f64,
+        /// How close to get, in tiles
+        radius: f64,
+    },
+    /// `pathfind`, which moves to a position along the ground path
+    Pathfind {
+        /// The x position, in tiles
+        x: f64,
+        /// The y position, in tiles
+        y: f64,
+    },
+    /// `autoPathfind`, which follows the path enemy ground units take
+    AutoPathfind,
+    /// `boost`, for units that can hover over blocks
+    Boost(bool),
+    /// `target`, which aims at a position
+    Target {
+        /// The x position, in tiles
+        x: f64,
+        /// The y position, in tiles
+        y: f64,
+        /// Whether to shoot
+        shoot: bool,
+    },
+    /// `targetp`, which aims at a unit, leading the shots
+    TargetP {
+        /// The unit to aim at
+        unit: Value,
+        /// Whether to shoot
+        shoot: bool,
+    },
+    /// `itemDrop`
+    ItemDrop {
+        /// The building to drop the items into
+        to: Value,
+        /// The number of items
+        amount: f64,
+    },
+    /// `itemTake`
+    ItemTake {
+        /// The building to take the items from
+        from: Value,
+        /// The type of item
+        item: Value,
+        /// The number of items
+        amount: f64,
+    },
+    /// `payDrop`
+    PayDrop,
+    /// `payTake`
+    PayTake {
+        /// Whether to pick up units instead of blocks
+        units: bool,
+    },
+    /// `payEnter`, which lands on the payload block below the unit
+    PayEnter,
+    /// `mine`
+    Mine {
+        /// The x position of the ore, in tiles
+        x: f64,
+        /// The y position of the ore, in tiles
+        y: f64,
+    },
+    /// `flag`, which sets the unit's flag
+    Flag(f64),
+    /// `build`
+    Build {
+        /// The x position, in tiles
+        x: f64,
+        /// The y position, in tiles
+        y: f64,
+        /// The type of block
+        block: Value,
+        /// The rotation, from 0 to 3
+        rotation: f64,
+        /// The configuration of the block
+        config: Value,
+    },
+    /// `getBlock`, which gives the block type, building and floor at a position
+    GetBlock {
+        /// The x position, in tiles
+        x: f64,
+        /// The y position, in tiles
+        y: f64,
+    },
+    /// `within`, which gives whether the unit is within `radius` of a position
+    Within {
+        /// The x position, in tiles
+        x: f64,
+        /// The y position, in tiles
+        y: f64,
+        /// The distance to check, in tiles
+        radius: f64,
+    },
+    /// `unbind`, which gives the unit back to its usual AI
+    Unbind,
+    /// `deconstruct`
+    Deconstruct {
+        /// The x position, in tiles
+        x: f64,
+        /// The y position, in tiles
+        y: f64,
+    },
+}
+
+/// A command given to a building by `control`
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ControlCommand {
+    /// `enabled`
+    Enabled(bool),
+    /// `config`, like the item a sorter lets through
+    Config(Value),
+    /// `color`, the packed colour of an illuminator
+    Colour(f64),
+    /// `shoot`, which aims a turret at a position
+    Shoot {
+        /// The x position, in tiles
+        x: f64,
+        /// The y position, in tiles
+        y: f64,
+        /// Whether to shoot
+        shoot: bool,
+    },
+    /// `shootp`, which aims a turret at a unit, leading the shots
+    ShootP {
+        /// The unit to aim at
+        unit: Value,
+        /// Whether to shoot
+        shoot: bool,
+    },
+}
+
+/// A change made to the world by a world processor.
+///
+/// Keywords that pick a variant of an instruction (like the `wave` in `setrule wave`) are kept as
+/// the names the game uses.
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum WorldEvent {
+    /// `setblock`, where `layer` is `floor`, `ore` or `block`. Only blocks have a team and
+    /// rotation.
+    SetBlock {
+        /// The layer being set
+        layer: String,
+        /// The x position, in tiles
+        x: f64,
+        /// The y position, in tiles
+        y: f64,
+        /// The block, floor or ore to place
+        block: Value,
+        /// The team of the block
+        team: Value,
+        /// The rotation of the block
+        rotation: f64,
+    },
+    /// `spawn`
+    SpawnUnit {
+        /// The type of unit
+        unit_type: Value,
+        /// The x position, in tiles
+        x: f64,
+        /// The y position, in tiles
+        y: f64,
+        /// The rotation, in degrees
+        rotation: f64,
+        /// The team of the unit
+        team: Value,
+    },
+    /// `spawnwave`
+    SpawnWave {
+        /// The x position, in tiles
+        x: f64,
+        /// The y position, in tiles
+        y: f64,
+        /// Whether the wave is spawned at the usual spawn points instead
+        natural: bool,
+    },
+    /// `explosion`
+    Explosion {
+        /// The team the explosion belongs to
+        team: Value,
+        /// The x position, in tiles
+        x: f64,
+        /// The y position, in tiles
+        y: f64,
+        /// The radius, in tiles
+        radius: f64,
+        /// The damage dealt
+        damage: f64,
+        /// Whether air units are hit
+        air: bool,
+        /// Whether ground units are hit
+        ground: bool,
+        /// Whether buildings are hit
+        pierce: bool,
+        /// Whether the explosion has an effect
+        effect: bool,
+    },
+    /// `status`, which applies a status effect or clears it
+    ApplyStatus {
+        /// Whether the status is removed instead of applied
+        clear: bool,
+        /// The status effect, like `wet` or `burning`
+        status: String,
+        /// The unit affected
+        unit: Value,
+        /// How long the status lasts, in seconds
+        duration: f64,
+    },
+    /// `setrule`, where `team` is `null` for rules that apply to every team
+    SetRule {
+        /// The rule, like `waveSpacing`
+        rule: String,
+        /// The new value
+        value: Value,
+        /// The team the rule is set for
+        team: Value,
+    },
+    /// `setrule mapArea`
+    SetMapArea {
+        /// The x position of the corner, in tiles
+        x: f64,
+        /// The y position of the corner, in tiles
+        y: f64,
+        /// The width, in tiles
+        w: f64,
+        /// The height, in tiles
+        h: f64,
+    },
+    /// `effect`. The rotation is the size for effects that have one, and operands an effect
+    /// doesn't use are 0 (or `null` for `data`).
+    Effect {
+        /// The effect, like `warn` or `shootBig`
+        effect: String,
+        /// The x position, in tiles
+        x: f64,
+        /// The y position, in tiles
+        y: f64,
+        /// The rotation or size
+        rotation: f64,
+        /// The packed colour
+        colour: f64,
+        /// Extra data, like the block for `blockFall`
+        data: Value,
+    },
+    /// `setprop`
+    SetProp {
+        /// The property, like `@copper` or `@health`
+        property: Value,
+        /// The building or unit changed
+        target: Value,
+        /// The new value
+        value: Value,
+    },
+    /// `message`, which shows the text buffer and clears it
+    Message {
+        /// Where the message is shown: `announce`, `notify`, `toast` or `mission`
+        kind: String,
+        /// How long it's shown for, in seconds
+        duration: f64,
+        /// The text shown
+        text: String,
+    },
+    /// `weatherset`
+    WeatherSet {
+        /// The weather, like `@rain`
+        weather: Value,
+        /// Whether it's turned on
+        active: bool,
+    },
+    /// `setflag`
+    SetFlag {
+        /// The name of the flag
+        flag: Value,
+        /// Whether it's set
+        value: bool,
+    },
+    /// `setrate`
+    SetRate {
+        /// The new number of instructions run per tick
+        ipt: f64,
+    },
+    /// `sync`, which sends a variable to every client
+    Sync {
+        /// The variable being synced
+        variable: String,
+        /// Its value
+        value: Value,
+    },
+    /// `cutscene pan`
+    CutscenePan {
+        /// The x position, in tiles
+        x: f64,
+        /// The y position, in tiles
+        y: f64,
+        /// How fast the camera moves
+        speed: f64,
+    },
+    /// `cutscene zoom`
+    CutsceneZoom {
+        /// The zoom level
+        level: f64,
+    },
+    /// `cutscene stop`
+    CutsceneStop,
+    /// `playsound`, where `position` is set for positional sounds
+    PlaySound {
+        /// The sound, like `@sfx-shoot`
+        sound: Value,
+        /// The volume
+        volume: f64,
+        /// The pitch
+        pitch: f64,
+        /// The pan of global sounds
+        pan: f64,
+        /// Where positional sounds come from
+        position: Option<(f64, f64)>,
+        /// Whether the sound is limited to playing once per frame
+        limit: bool,
+    },
+    /// `makemarker`
+    MakeMarker {
+        /// The kind of marker, like `shape` or `text`
+        kind: String,
+        /// The id of the marker
+        id: f64,
+        /// The x position, in tiles
+        x: f64,
+        /// The y position, in tiles
+        y: f64,
+        /// Whether an existing marker with the same id is replaced
+        replace: bool,
+    },
+    /// `setmarker`, with the operands in the order they're written
+    SetMarker {
+        /// The property, like `pos` or `color`
+        property: String,
+        /// The id of the marker
+        id: f64,
+        /// The values used
+        values: Vec<Value>,
+    },
+}
+
+/// Something a world processor looks up in the world
+#[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum WorldQuery {
+    /// `getblock`, where `layer` is `floor`, `ore`, `block` or `building`
+    GetBlock {
+        /// The layer to look at
+        layer: String,
+        /// The x position, in tiles
+        x: f64,
+        /// The y position, in tiles
+        y: f64,
+    },
+    /// `fetch`. Counts (like `unitCount`) ignore the index.
+    Fetch {
+        /// What to fetch, like `unit` or `coreCount`
+        kind: String,
+        /// The team to look in
+        team: Value,
+        /// Which one to get
+        index: f64,
+        /// The type of unit or block to look for, if the kind has one
+        filter: Value,
+    },
+    /// `weathersense`, which checks if a weather is active
+    WeatherSense {
+        /// The weather, like `@rain`
+        weather: Value,
+    },
+    /// `getflag`
+    GetFlag {
+        /// The name of the flag
+        flag: Value,
+    },
+    /// `localeprint`, which gets a map locale bundle entry
+    Locale {
+        /// The key of the entry
+        key: Value,
+    },
+}
@@ -0,0 +1,495 @@
+use super::clock::MAX_IPT;
+use super::trace::Effect;
+use super::world::{World, WorldEvent, WorldQuery};
+use super::{Instruction, Processor, Value, name};
+use crate::parser::{args::Argument, statements::WprocStatement};
+
+impl<'a, W: World, S: Instruction<'a>> Processor<'a, W, S> {
+    /// Runs a statement that only world processors can run
+    pub(super) fn execute_wproc(&mut self, statement: WprocStatement<'a>) {
+        if let Some(query) = self.world_query(&statement) {
+            let result = match statement {
+                WprocStatement::GetBFloor { result, .. }
+                | WprocStatement::GetBOre { result, .. }
+                | WprocStatement::GetBBlock { result, .. }
+                | WprocStatement::GetBBuilding { result, .. }
+                | WprocStatement::FetchUnit { result, .. }
+                | WprocStatement::FetchUnitCount { result, .. }
+                | WprocStatement::FetchPlayer { result, .. }
+                | WprocStatement::FetchPlayerCount { result, .. }
+                | WprocStatement::FetchCore { result, .. }
+                | WprocStatement::FetchCoreCount { result, .. }
+                | WprocStatement::FetchBuild { result, .. }
+                | WprocStatement::FetchBuildCount { result, .. }
+                | WprocStatement::WeatherSense { result, .. } => Some(result),
+                WprocStatement::GetFlag { value, .. } => Some(value),
+                _ => None,
+            };
+            let value = self.world.query(&query);
+
+            match result {
+                Some(result) => self.write(result, value),
+                // Only `localeprint` has no output, and prints what it finds instead
+                None if self.can_print() => self.text_buffer.push_str(&value.to_string()),
+                None => {}
+            }
+        } else if let Some(event) = self.world_event(&statement) {
+            let value = self.world.apply(&event);
+            if let WorldEvent::SetRate { ipt } = event {
+                self.ipt = ipt.floor().clamp(1., MAX_IPT);
+            }
+            self.record(Effect::World(event));
+
+            match statement {
+                WprocStatement::SpawnUnit { result, .. } => self.write(result, value),
+                WprocStatement::ShowMessage {
+                    success: Argument::Variable(success),
+                    ..
+                } => self.write(success, Value::bool(true)),
+                _ => {}
+            }
+        }
+    }
+
+    /// Turns a statement into what it looks up in the world, if it looks anything up
+    fn world_query(&self, statement: &WprocStatement) -> Option<WorldQuery> {
+        let num = |arg: &Argument| self.read(arg).num();
+
+        Some(match statement {
+            WprocStatement::GetBFloor { x, y, .. }
+            | WprocStatement::GetBOre { x, y, .. }
+            | WprocStatement::GetBBlock { x, y, .. }
+            | WprocStatement::GetBBuilding { x, y, .. } => WorldQuery::GetBlock {
+                layer: keyword(statement),
+                x: num(x),
+                y: num(y),
+            },
+            WprocStatement::FetchUnit {
+                team,
+                number,
+                unit: filter,
+                ..
+            }
+            | WprocStatement::FetchBuild {
+                team,
+                number,
+                block: filter,
+                ..
+            } => WorldQuery::Fetch {
+                kind: keyword(statement),
+                team: self.read(team),
+                index: num(number),
+                filter: self.read(filter),
+            },
+            WprocStatement::FetchPlayer { team, number, .. }
+            | WprocStatement::FetchCore { team, number, .. } => WorldQuery::Fetch {
+                kind: keyword(statement),
+                team: self.read(team),
+                index: num(number),
+                filter: Value::Null,
+            },
+            WprocStatement::FetchUnitCount {
+                team, unit: filter, ..
+            }
+            | WprocStatement::FetchBuildCount {
+                team,
+                block: filter,
+                ..
+            } => WorldQuery::Fetch {
+                kind: keyword(statement),
+                team: self.read(team),
+                index: 0.,
+                filter: self.read(filter),
+            },
+            WprocStatement::FetchPlayerCount { team, .. }
+            | WprocStatement::FetchCoreCount { team, .. } => WorldQuery::Fetch {
+                kind: keyword(statement),
+                team: self.read(team),
+                index: 0.,
+                filter: Value::Null,
+            },
+            WprocStatement::WeatherSense { weather, .. } => WorldQuery::WeatherSense {
+                weather: self.read(weather),
+            },
+            WprocStatement::GetFlag { flag, .. } => WorldQuery::GetFlag {
+                flag: self.read(flag),
+            },
+            WprocStatement::LocalePrint { property } => WorldQuery::Locale {
+                key: self.read(property),
+            },
+            _ => return None,
+        })
+    }
+
+    /// Turns a statement into the change it makes to the world, if it changes anything
+    #[allow(clippy::too_many_lines)]
+    fn world_event(&mut self, statement: &WprocStatement) -> Option<WorldEvent> {
+        let num = |arg: &Argument| self.read(arg).num();
+        let truthy = |arg: &Argument| self.read(arg).is_truthy();
+        let effect = |x: &Argument, y: &Argument, rotation: f64, colour: f64, data: Value| {
+            WorldEvent::Effect {
+                effect: keyword(statement),
+                x: num(x),
+                y: num(y),
+                rotation,
+                colour,
+                data,
+            }
+        };
+
+        Some(match statement {
+            WprocStatement::SetBOre { to, x, y } | WprocStatement::SetBFloor { to, x, y } => {
+                WorldEvent::SetBlock {
+                    layer: keyword(statement),
+                    x: num(x),
+                    y: num(y),
+                    block: self.read(to),
+                    team: Value::Null,
+                    rotation: 0.,
+                }
+            }
+            WprocStatement::SetBBlock {
+                to,
+                x,
+                y,
+                team,
+                rotation,
+            } => WorldEvent::SetBlock {
+                layer: keyword(statement),
+                x: num(x),
+                y: num(y),
+                block: self.read(to),
+                team: self.read(team),
+                rotation: num(rotation),
+            },
+            WprocStatement::SpawnUnit {
+                unit_type,
+                x,
+                y,
+                rotation,
+                team,
+                ..
+            } => WorldEvent::SpawnUnit {
+                unit_type: self.read(unit_type),
+                x: num(x),
+                y: num(y),
+                rotation: num(rotation),
+                team: self.read(team),
+            },
+            WprocStatement::SpawnWave { x, y, natural } => WorldEvent::SpawnWave {
+                x: num(x),
+                y: num(y),
+                natural: truthy(natural),
+            },
+            WprocStatement::Explosion {
+                team,
+                x,
+                y,
+                radius,
+                damage,
+                air,
+                ground,
+                pierce,
+                effect,
+            } => WorldEvent::Explosion {
+                team: self.read(team),
+                x: num(x),
+                y: num(y),
+                radius: num(radius),
+                damage: num(damage),
+                air: truthy(air),
+                ground: truthy(ground),
+                pierce: truthy(pierce),
+                effect: truthy(effect),
+            },
+            WprocStatement::ApplyStatus {
+                _padding,
+                wet,
+                unit,
+                duration,
+            } => WorldEvent::ApplyStatus {
+                clear: truthy(_padding),
+                status: name(wet),
+                unit: self.read(unit),
+                duration: num(duration),
+            },
+            WprocStatement::SetRCurrentWaveTime { v }
+            | WprocStatement::SetRWaveTimer { v }
+            | WprocStatement::SetRWaves { v }
+            | WprocStatement::SetRWave { v }
+            | WprocStatement::SetRWaveSpacing { v }
+            | WprocStatement::SetRWaveSending { v }
+            | WprocStatement::SetRAttackMode { v }
+            | WprocStatement::SetREnemyCoreBuildRadius { v }
+            | WprocStatement::SetRDropZoneRadius { v }
+            | WprocStatement::SetRUnitCap { v }
+            | WprocStatement::SetRLighting { v }
+            | WprocStatement::SetRCanGameOver { v }
+            | WprocStatement::SetRAmbientLight { v }
+            | WprocStatement::SetRSolarMultiplier { multiplier: v }
+            | WprocStatement::SetRDragMultiplier { multiplier: v }
+            | WprocStatement::SetRBan { index: v }
+            | WprocStatement::SetRUnban { index: v } => WorldEvent::SetRule {
+                rule: keyword(statement),
+                value: self.read(v),
+                team: Value::Null,
+            },
+            WprocStatement::SetRBuildSpeed { v, team }
+            | WprocStatement::SetRUnitHealth { v, team }
+            | WprocStatement::SetRUnitBuildSpeed { v, team }
+            | WprocStatement::SetRUnitMineSpeed { v, team }
+            | WprocStatement::SetRUnitCost { v, team }
+            | WprocStatement::SetRUnitDamage { v, team }
+            | WprocStatement::SetRBlockHealth { v, team }
+            | WprocStatement::SetRBlockDamage { v, team }
+            | WprocStatement::SetRRtsMinWeight { v, team }
+            | WprocStatement::SetRRtsMinSquad { v, team } => WorldEvent::SetRule {
+                rule: keyword(statement),
+                value: self.read(v),
+                team: self.read(team),
+            },
+            WprocStatement::SetRMapArea { x, y, w, h, .. } => WorldEvent::SetMapArea {
+                x: num(x),
+                y: num(y),
+                w: num(w),
+                h: num(h),
+            },
+            WprocStatement::EffectWarn { x, y }
+            | WprocStatement::EffectCross { x, y }
+            | WprocStatement::EffectSpawn { x, y }
+            | WprocStatement::EffectBubble { x, y } => effect(x, y, 0., 0., Value::Null),
+            WprocStatement::EffectPlaceBlock { x, y, size }
+            | WprocStatement::EffectPlaceBlockS { x, y, size }
+            | WprocStatement::EffectBreakBlock { x, y, size }
+            | WprocStatement::EffectExplosion { x, y, size } => {
+                effect(x, y, num(size), 0., Value::Null)
+            }
+            WprocStatement::EffectTrail { x, y, size, colour }
+            | WprocStatement::EffectBreakProp { x, y, size, colour }
+            | WprocStatement::EffectWave { x, y, size, colour }
+            | WprocStatement::EffectLightBlock { x, y, size, colour }
+            | WprocStatement::EffectCrossExplosion { x, y, size, colour }
+            | WprocStatement::EffectShootBig {
+                x,
+                y,
+                rotation: size,
+                colour,
+            }
+            | WprocStatement::EffectShootSmall {
+                x,
+                y,
+                rotation: size,
+                colour,
+            }
+            | WprocStatement::EffectSmokeColour {
+                x,
+                y,
+                rotation: size,
+                colour,
+            }
+            | WprocStatement::EffectSmokeSquare {
+                x,
+                y,
+                rotation: size,
+                colour,
+            }
+            | WprocStatement::EffectSmokeSquareBig {
+                x,
+                y,
+                rotation: size,
+                colour,
+            }
+            | WprocStatement::EffectSparkShoot {
+                x,
+                y,
+                rotation: size,
+                colour,
+            }
+            | WprocStatement::EffectSparkShootBig {
+                x,
+                y,
+                rotation: size,
+                colour,
+            } => effect(x, y, num(size), num(colour), Value::Null),
+            WprocStatement::EffectSmokeCloud { x, y, colour, .. }
+            | WprocStatement::EffectVapour { x, y, colour, .. }
+            | WprocStatement::EffectHit { x, y, colour, .. }
+            | WprocStatement::EffectHitSquare { x, y, colour, .. }
+            | WprocStatement::EffectSmokePuff { x, y, colour, .. }
+            | WprocStatement::EffectSmokeSmall { x, y, colour, .. }
+            | WprocStatement::EffectSmokeBig { x, y, colour, .. }
+            | WprocStatement::EffectSpark { x, y, colour, .. }
+            | WprocStatement::EffectSparkBig { x, y, colour, .. }
+            | WprocStatement::EffectDrill { x, y, colour, .. }
+            | WprocStatement::EffectDrillBig { x, y, colour, .. }
+            | WprocStatement::EffectSparkExplosion { x, y, colour, .. } => {
+                effect(x, y, 0., num(colour), Value::Null)
+            }
+            WprocStatement::EffectBlockFall { x, y, data, .. } => {
+                effect(x, y, 0., 0., self.read(data))
+            }
+            WprocStatement::SetProp { prop, block, value } => WorldEvent::SetProp {
+                property: self.read(prop),
+                target: self.read(block),
+                value: self.read(value),
+            },
+            WprocStatement::ShowMessage {
+                msg_type, duration, ..
+            } => WorldEvent::Message {
+                kind: name(msg_type),
+                duration: num(duration),
+                text: std::mem::take(&mut self.text_buffer),
+            },
+            WprocStatement::WeatherSet { weather, state } => WorldEvent::WeatherSet {
+                weather: self.read(weather),
+                active: truthy(state),
+            },
+            WprocStatement::SetFlag { value, flag } => WorldEvent::SetFlag {
+                flag: self.read(&Argument::from(*flag)),
+                value: truthy(value),
+            },
+            WprocStatement::SetRate { rate } => WorldEvent::SetRate { ipt: num(rate) },
+            WprocStatement::Sync { var } => WorldEvent::Sync {
+                variable: name(var),
+                value: self.read(var),
+            },
+            WprocStatement::CutscenePan { x, y, speed } => WorldEvent::CutscenePan {
+                x: num(x),
+                y: num(y),
+                speed: num(speed),
+            },
+            WprocStatement::CutsceneZoom { level } => {
+                WorldEvent::CutsceneZoom { level: num(level) }
+            }
+            WprocStatement::CutsceneStop {} => WorldEvent::CutsceneStop,
+            WprocStatement::PlaySoundPositional {
+                sound,
+                volume,
+                pitch,
+                x,
+                y,
+                limit,
+                ..
+            } => WorldEvent::PlaySound {
+                sound: self.read(sound),
+                volume: num(volume),
+                pitch: num(pitch),
+                pan: 0.,
+                position: Some((num(x), num(y))),
+                limit: truthy(limit),
+            },
+            WprocStatement::PlaySoundGlobal {
+                sound,
+                volume,
+                pitch,
+                pan,
+                limit,
+                ..
+            } => WorldEvent::PlaySound {
+                sound: self.read(sound),
+                volume: num(volume),
+                pitch: num(pitch),
+                pan: num(pan),
+                position: None,
+                limit: truthy(limit),
+            },
+            WprocStatement::MakeMarkerShapeText { id, x, y, replace }
+            | WprocStatement::MakeMarkerPoint { id, x, y, replace }
+            | WprocStatement::MakeMarkerShape { id, x, y, replace }
+            | WprocStatement::MakeMarkerText { id, x, y, replace }
+            | WprocStatement::MakeMarkerLine { id, x, y, replace }
+            | WprocStatement::MakeMarkerTexture { id, x, y, replace }
+            | WprocStatement::MakeMarkerQuad { id, x, y, replace } => WorldEvent::MakeMarker {
+                kind: keyword(statement),
+                id: num(id),
+                x: num(x),
+                y: num(y),
+                replace: truthy(replace),
+            },
+            WprocStatement::SetMarkerRemove { id } => self.set_marker(statement, id, &[]),
+            WprocStatement::SetMarkerWorld { id, val }
+            | WprocStatement::SetMarkerMinimap { id, val }
+            | WprocStatement::SetMarkerDrawLayer { id, layer: val }
+            | WprocStatement::SetMarkerAutoscale { id, val }
+            | WprocStatement::SetMarkerLayer { id, val }
+            | WprocStatement::SetMarkerColour { id, val }
+            | WprocStatement::SetMarkerStroke { id, val }
+            | WprocStatement::SetMarkerOutline { id, val }
+            | WprocStatement::SetMarkerRadius { id, val }
+            | WprocStatement::SetMarkerRotation { id, val }
+            | WprocStatement::SetMarkerFontSize { id, size: val }
+            | WprocStatement::SetMarkerTextHeight { id, height: val }
+            | WprocStatement::SetMarkerTextAlign { id, align: val }
+            | WprocStatement::SetMarkerLineAlign { id, align: val }
+            | WprocStatement::SetMarkerFlushText { id, fetch: val } => {
+                self.set_marker(statement, id, &[val])
+            }
+            WprocStatement::SetMarkerPos { id, x, y }
+            | WprocStatement::SetMarkerEndPos { id, x, y }
+            | WprocStatement::SetMarkerArc {
+                id,
+                start: x,
+                end: y,
+            }
+            | WprocStatement::SetMarkerLabelFlags {
+                id,
+                background: x,
+                outline: y,
+            }
+            | WprocStatement::SetMarkerTexture {
+                id,
+                printflush: x,
+                name: y,
+            }
+            | WprocStatement::SetMarkerTexSize {
+                id,
+                width: x,
+                height: y,
+            }
+            | WprocStatement::SetMarkerColourI {
+                id,
+                index: x,
+                colour: y,
+            } => self.set_marker(statement, id, &[x, y]),
+            WprocStatement::SetMarkerShape {
+                id,
+                sides: a,
+                fill: b,
+                radius: c,
+            }
+            | WprocStatement::SetMarkerPosI {
+                id,
+                index: a,
+                x: b,
+                y: c,
+            }
+            | WprocStatement::SetMarkerUVI {
+                id,
+                index: a,
+                x: b,
+                y: c,
+            } => self.set_marker(statement, id, &[a, b, c]),
+            _ => return None,
+        })
+    }
+
+    /// Builds the event for a `setmarker` statement
+    fn set_marker(
+        &self,
+        statement: &WprocStatement,
+        id: &Argument,
+        values: &[&Argument],
+    ) -> WorldEvent {
+        WorldEvent::SetMarker {
+            property: keyword(statement),
+            id: self.read(id).num(),
+            values: values.iter().map(|x| self.read(x)).collect(),
+        }
+    }
+}
+
+/// Gets the keyword picking the variant of an instruction, like the `wave` in `setrule wave`
+fn keyword(statement: &WprocStatement) -> String {
+    let printed = statement.to_string();
+    printed.split(' ').nth(1).unwrap_or_default().to_string()
+}